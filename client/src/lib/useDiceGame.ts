@@ -1,9 +1,27 @@
 import { useConnection } from '@solana/wallet-adapter-react'
 import { useAnchorWallet } from '@solana/wallet-adapter-react'
-import { PublicKey, SystemProgram, LAMPORTS_PER_SOL } from '@solana/web3.js'
+import { PublicKey, SystemProgram, LAMPORTS_PER_SOL, AccountMeta } from '@solana/web3.js'
 import { BN } from '@coral-xyz/anchor'
 import { getProgram } from './anchorProgram'
 
+/**
+ * Game settings beyond the entry fee and seat count; each has a default
+ */
+export interface CreateGameOptions {
+  minPlayers?: number
+  randomness?: 'slotHashes' | 'oracle'
+  tieBreak?: 'split' | 'rollOff'
+  payoutTiers?: number[]
+  creatorRakeBps?: number
+  referrer?: PublicKey | null
+  joinWindow?: number
+  rollWindow?: number
+  claimWindow?: number
+}
+
+const HOUR = 60 * 60
+const DAY = 24 * HOUR
+
 /**
  * Custom hook for Dice Game methods
  * Follows the Battle-Block pattern for method calls
@@ -26,17 +44,69 @@ export function useDiceGame() {
   }
 
   /**
-   * Create a new game
+   * Derive a player's randomness request PDA for the given round (oracle games)
+   */
+  const getRandomnessRequestPDA = (
+    programId: PublicKey,
+    gameAccount: PublicKey,
+    player: PublicKey,
+    round: number
+  ): PublicKey => {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('randomness_request'),
+        gameAccount.toBuffer(),
+        player.toBuffer(),
+        Buffer.from([round]),
+      ],
+      programId
+    )[0]
+  }
+
+  /**
+   * Referral PDAs of referred players, which any transaction that may settle the game
+   * has to pass as remaining accounts
+   */
+  const getReferralAccounts = (programId: PublicKey, game: any): AccountMeta[] => {
+    return (game.players as PublicKey[])
+      .filter((_, i) => game.referrers[i] !== null)
+      .map((player) => ({
+        pubkey: PublicKey.findProgramAddressSync(
+          [Buffer.from('referral'), player.toBuffer()],
+          programId
+        )[0],
+        isSigner: false,
+        isWritable: true,
+      }))
+  }
+
+  /**
+   * Create a new lamport game
    */
   const createGame = async (
     gameId: BN,
     entryFee: BN,
-    maxPlayers: number
+    maxPlayers: number,
+    options: CreateGameOptions = {}
   ): Promise<string> => {
+    const {
+      minPlayers = 2,
+      randomness = 'slotHashes',
+      tieBreak = 'split',
+      payoutTiers = [10_000],
+      creatorRakeBps = 0,
+      referrer = null,
+      joinWindow = DAY,
+      rollWindow = HOUR,
+      claimWindow = 30 * DAY,
+    } = options
+
     console.log('Creating game with:', {
       gameId: gameId.toString(),
       entryFee: entryFee.toString(),
-      maxPlayers
+      maxPlayers,
+      minPlayers,
+      randomness
     })
 
     if (!wallet) {
@@ -53,12 +123,33 @@ export function useDiceGame() {
       const [gameAccount] = await getGameAccountPDA(gameId)
       console.log('Game account PDA:', gameAccount.toBase58())
 
-      // Build and send the transaction
+      // Build and send the transaction. Commit-reveal and collaborative games need a seed
+      // commitment, so they aren't offered here
       const tx = await program.methods
-        .createGame(gameId, entryFee, maxPlayers)
+        .createGame(
+          gameId,
+          entryFee,
+          maxPlayers,
+          minPlayers,
+          { [randomness]: {} },
+          null,
+          { [tieBreak]: {} },
+          payoutTiers,
+          creatorRakeBps,
+          referrer,
+          {
+            joinWindow: new BN(joinWindow),
+            rollWindow: new BN(rollWindow),
+            claimWindow: new BN(claimWindow),
+          }
+        )
         .accounts({
           gameAccount: gameAccount,
           creator: wallet.publicKey,
+          mint: null,
+          vault: null,
+          creatorTokenAccount: null,
+          tokenProgram: null,
           systemProgram: SystemProgram.programId,
         })
         .rpc()
@@ -79,7 +170,10 @@ export function useDiceGame() {
   /**
    * Join an existing game
    */
-  const joinGame = async (gameId: BN): Promise<string> => {
+  const joinGame = async (
+    gameId: BN,
+    referrer: PublicKey | null = null
+  ): Promise<string> => {
     console.log('Joining game:', gameId.toString())
 
     if (!wallet) {
@@ -98,10 +192,14 @@ export function useDiceGame() {
 
       // Build and send the transaction
       const tx = await program.methods
-        .joinGame()
+        .joinGame(null, referrer)
         .accounts({
           gameAccount: gameAccount,
           player: wallet.publicKey,
+          mint: null,
+          vault: null,
+          playerTokenAccount: null,
+          tokenProgram: null,
           systemProgram: SystemProgram.programId,
         })
         .rpc()
@@ -181,14 +279,22 @@ export function useDiceGame() {
       const [gameAccount] = await getGameAccountPDA(gameId)
       console.log('Game account PDA:', gameAccount.toBase58())
 
+      // Oracle games open a randomness request; the last roll may settle the game
+      const game = await (program.account as any).gameAccount.fetch(gameAccount)
+      const randomnessRequest = game.randomness.oracle
+        ? getRandomnessRequestPDA(program.programId, gameAccount, wallet.publicKey, game.round)
+        : null
+
       // Build and send the transaction
       const tx = await program.methods
         .rollDice()
         .accounts({
           gameAccount: gameAccount,
           player: wallet.publicKey,
+          randomnessRequest,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(getReferralAccounts(program.programId, game))
         .rpc()
 
       console.log('Roll dice transaction successful:', tx)
@@ -224,6 +330,8 @@ export function useDiceGame() {
       const [gameAccount] = await getGameAccountPDA(gameId)
       console.log('Game account PDA:', gameAccount.toBase58())
 
+      const game = await (program.account as any).gameAccount.fetch(gameAccount)
+
       // Build and send the transaction
       const tx = await program.methods
         .finalizeGame()
@@ -231,6 +339,7 @@ export function useDiceGame() {
           gameAccount: gameAccount,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(getReferralAccounts(program.programId, game))
         .rpc()
 
       console.log('Finalize game transaction successful:', tx)
@@ -272,6 +381,11 @@ export function useDiceGame() {
         .accounts({
           gameAccount: gameAccount,
           player: wallet.publicKey,
+          mint: null,
+          vault: null,
+          playerTokenAccount: null,
+          tokenProgram: null,
+          memoProgram: null,
           systemProgram: SystemProgram.programId,
         })
         .rpc()
@@ -373,6 +487,11 @@ export function useDiceGame() {
         .accounts({
           gameAccount: gameAccount,
           winner: wallet.publicKey,
+          mint: null,
+          vault: null,
+          winnerTokenAccount: null,
+          tokenProgram: null,
+          memoProgram: null,
           systemProgram: SystemProgram.programId,
         })
         .rpc()
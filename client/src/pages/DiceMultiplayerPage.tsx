@@ -172,7 +172,8 @@ const DiceMultiplayerPage = () => {
       const tx = await createGame(
         newGameId,
         entryFee,
-        MAX_PLAYERS
+        MAX_PLAYERS,
+        { minPlayers: MIN_PLAYERS }
       )
 
       // Calculate game account PDA with correct program ID
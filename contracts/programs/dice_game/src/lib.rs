@@ -1,11 +1,21 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::clock::Clock;
-use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::sysvar::slot_hashes;
 
 declare_id!("6TMNF6Wrw7PCBbRgJUJSzQBn8g43XZ4Y28pSFGRX5jER");
 
 const PLATFORM_FEE_BPS: u16 = 250; // 2.5% platform fee
 const MAX_PLAYERS: usize = 6;
+// Bounds on how long a game may wait for players before they can withdraw
+const MIN_JOIN_WINDOW_SECONDS: i64 = 5 * 60;
+const MAX_JOIN_WINDOW_SECONDS: i64 = 7 * 24 * 60 * 60;
+// Bounds on how long players have to roll once the game starts
+const MIN_ROLL_WINDOW_SECONDS: i64 = 60;
+const MAX_ROLL_WINDOW_SECONDS: i64 = 24 * 60 * 60;
+// Bounds on how long winners have to claim once the game completes
+const MIN_CLAIM_WINDOW_SECONDS: i64 = 60 * 60;
+const MAX_CLAIM_WINDOW_SECONDS: i64 = 90 * 24 * 60 * 60;
 
 #[program]
 pub mod dice_game {
@@ -17,9 +27,24 @@ pub mod dice_game {
         game_id: u64,
         entry_fee: u64,
         max_players: u8,
+        join_window: i64,
+        roll_window: i64,
+        claim_window: i64,
     ) -> Result<()> {
         require!(max_players >= 2 && max_players <= 6, GameError::InvalidMaxPlayers);
         require!(entry_fee > 0, GameError::InvalidEntryFee);
+        require!(
+            (MIN_JOIN_WINDOW_SECONDS..=MAX_JOIN_WINDOW_SECONDS).contains(&join_window),
+            GameError::InvalidJoinWindow
+        );
+        require!(
+            (MIN_ROLL_WINDOW_SECONDS..=MAX_ROLL_WINDOW_SECONDS).contains(&roll_window),
+            GameError::InvalidRollWindow
+        );
+        require!(
+            (MIN_CLAIM_WINDOW_SECONDS..=MAX_CLAIM_WINDOW_SECONDS).contains(&claim_window),
+            GameError::InvalidClaimWindow
+        );
 
         let game = &mut ctx.accounts.game_account;
         game.game_id = game_id;
@@ -30,6 +55,12 @@ pub mod dice_game {
         game.total_pool = entry_fee;
         game.status = GameStatus::Waiting;
         game.created_at = Clock::get()?.unix_timestamp;
+        game.join_deadline = game.created_at + join_window;
+        game.roll_window = roll_window;
+        game.roll_deadline = 0; // set when the game starts
+        game.claim_window = claim_window;
+        game.claim_deadline = 0; // set when the game completes
+        let join_deadline = game.join_deadline;
 
         // Initialize players array with creator
         game.players = vec![ctx.accounts.creator.key()];
@@ -56,6 +87,9 @@ pub mod dice_game {
             creator: ctx.accounts.creator.key(),
             entry_fee,
             max_players,
+            join_deadline,
+            roll_window,
+            claim_window,
         });
 
         Ok(())
@@ -69,6 +103,7 @@ pub mod dice_game {
         require!(game.status == GameStatus::Waiting, GameError::GameNotWaiting);
         require!(game.current_players < game.max_players, GameError::GameFull);
         require!(!game.players.contains(&ctx.accounts.player.key()), GameError::AlreadyJoined);
+        require!(Clock::get()?.unix_timestamp <= game.join_deadline, GameError::JoinClosed);
 
         // Add player to the game
         game.players.push(ctx.accounts.player.key());
@@ -99,8 +134,10 @@ pub mod dice_game {
 
         // Start game if max players reached
         if should_start {
+            let now = Clock::get()?.unix_timestamp;
             game.status = GameStatus::Playing;
-            game.started_at = Some(Clock::get()?.unix_timestamp);
+            game.started_at = Some(now);
+            game.roll_deadline = now + game.roll_window;
         }
 
         emit!(PlayerJoined {
@@ -120,8 +157,10 @@ pub mod dice_game {
         require!(game.current_players >= 2, GameError::NotEnoughPlayers);
         require!(ctx.accounts.starter.key() == game.creator, GameError::NotCreator);
 
+        let now = Clock::get()?.unix_timestamp;
         game.status = GameStatus::Playing;
-        game.started_at = Some(Clock::get()?.unix_timestamp);
+        game.started_at = Some(now);
+        game.roll_deadline = now + game.roll_window;
 
         emit!(GameStarted {
             game_id: game.game_id,
@@ -133,6 +172,7 @@ pub mod dice_game {
 
     // Roll dice for a player
     pub fn roll_dice(ctx: Context<RollDice>) -> Result<()> {
+        let game_key = ctx.accounts.game_account.key();
         let game = &mut ctx.accounts.game_account;
         let player = ctx.accounts.player.key();
 
@@ -146,14 +186,18 @@ pub mod dice_game {
 
         require!(game.rolls[player_index].is_none(), GameError::AlreadyRolled);
 
-        // Generate pseudo-random dice rolls
+        // Generate pseudo-random dice rolls from the latest slot hash, which is not
+        // known until the previous slot is produced
         let clock = Clock::get()?;
-        let mut seed = Vec::new();
-        seed.extend_from_slice(&clock.unix_timestamp.to_le_bytes());
-        seed.extend_from_slice(player.as_ref());
-        seed.extend_from_slice(&game.game_id.to_le_bytes());
-
-        let hash_result = hash(&seed);
+        require!(clock.unix_timestamp <= game.roll_deadline, GameError::RollClosed);
+        let slot_hash = recent_slot_hash(&ctx.accounts.slot_hashes)?;
+        let roll_index = player_index as u64;
+        let hash_result = hashv(&[
+            &slot_hash,
+            game_key.as_ref(),
+            player.as_ref(),
+            &roll_index.to_le_bytes(),
+        ]);
         let dice1 = (hash_result.to_bytes()[0] % 6) + 1;
         let dice2 = (hash_result.to_bytes()[1] % 6) + 1;
         let total = dice1 + dice2;
@@ -189,7 +233,8 @@ pub mod dice_game {
         Ok(())
     }
 
-    // Finalize game and distribute prizes (called automatically after all rolls)
+    // Finalize game and distribute prizes (called automatically after all rolls). Once the
+    // roll deadline has passed, players who haven't rolled forfeit and it settles without them
     pub fn finalize_game(ctx: Context<FinalizeGame>) -> Result<()> {
         ctx.accounts.finalize_and_distribute()?;
         Ok(())
@@ -203,8 +248,7 @@ pub mod dice_game {
         require!(game.status == GameStatus::Waiting, GameError::GameStarted);
 
         let clock = Clock::get()?;
-        let expiry_time = game.created_at + 3600; // 1 hour expiry
-        require!(clock.unix_timestamp > expiry_time, GameError::NotExpired);
+        require!(clock.unix_timestamp > game.join_deadline, GameError::NotExpired);
 
         // Find player and refund entry fee
         if game.players.contains(&player) {
@@ -292,6 +336,10 @@ pub struct RollDice<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
 
+    /// CHECK: the SlotHashes sysvar, read in place because it is too large to deserialize
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -322,6 +370,19 @@ pub struct EmergencyWithdraw<'info> {
     pub system_program: Program<'info, System>,
 }
 
+// Newest SlotHashes entry. Each entry is a u64 slot followed by a 32 byte hash,
+// after a u64 entry count, so the first hash starts at byte 16.
+fn recent_slot_hash(slot_hashes_info: &AccountInfo) -> Result<[u8; 32]> {
+    let data = slot_hashes_info.try_borrow_data()?;
+    let len = data
+        .get(..8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or(GameError::SlotHashUnavailable)?;
+    require!(len > 0 && data.len() >= 48, GameError::SlotHashUnavailable);
+
+    Ok(data[16..48].try_into().unwrap())
+}
+
 // Account structures
 #[account]
 #[derive(InitSpace)]
@@ -334,6 +395,13 @@ pub struct GameAccount {
     pub total_pool: u64,
     pub status: GameStatus,
     pub created_at: i64,
+    pub join_deadline: i64,
+    // Windows chosen at create_game; rolls close roll_window after the start and claims
+    // claim_window after completion
+    pub roll_window: i64,
+    pub roll_deadline: i64,
+    pub claim_window: i64,
+    pub claim_deadline: i64,
     pub started_at: Option<i64>,
     pub completed_at: Option<i64>,
     #[max_len(6)]
@@ -349,14 +417,13 @@ impl<'info> FinalizeGame<'info> {
 
         require!(game.status == GameStatus::Playing, GameError::GameNotPlaying);
 
-        // Check all active players have rolled
-        let active_players_rolled = game.players
-            .iter()
-            .take(game.current_players as usize)
-            .enumerate()
-            .all(|(i, _)| game.rolls[i].is_some());
+        // Check all active players have rolled, or the roll deadline passed with a roll to rank
+        let rolls = &game.rolls[..game.current_players as usize];
+        let active_players_rolled = rolls.iter().all(|r| r.is_some());
+        let roll_closed = Clock::get()?.unix_timestamp > game.roll_deadline
+            && rolls.iter().any(|r| r.is_some());
 
-        require!(active_players_rolled, GameError::WaitingForRolls);
+        require!(active_players_rolled || roll_closed, GameError::WaitingForRolls);
 
         // Determine winner(s) - highest total wins
         let mut highest_total = 0u8;
@@ -382,14 +449,17 @@ impl<'info> FinalizeGame<'info> {
         // Set game winner (first winner if tie)
         // For MVP, we'll store the winner and prize amount for claiming later
         game.winner = Some(game.players[winner_indices[0]]);
+        let now = Clock::get()?.unix_timestamp;
         game.status = GameStatus::Completed;
-        game.completed_at = Some(Clock::get()?.unix_timestamp);
+        game.completed_at = Some(now);
+        game.claim_deadline = now + game.claim_window;
 
         emit!(GameCompleted {
             game_id: game.game_id,
             winner: game.winner.unwrap(),
             prize_amount: prize_per_winner,
             winner_roll: highest_total,
+            claim_deadline: game.claim_deadline,
         });
 
         Ok(())
@@ -426,14 +496,17 @@ impl<'info> RollDice<'info> {
         // For MVP simplicity, we'll just mark the winner and prize amount
         // The actual transfer happens in a claim instruction
         game.winner = Some(game.players[winner_indices[0]]);
+        let now = Clock::get()?.unix_timestamp;
         game.status = GameStatus::Completed;
-        game.completed_at = Some(Clock::get()?.unix_timestamp);
+        game.completed_at = Some(now);
+        game.claim_deadline = now + game.claim_window;
 
         emit!(GameCompleted {
             game_id: game.game_id,
             winner: game.winner.unwrap(),
             prize_amount: prize_per_winner,
             winner_roll: highest_total,
+            claim_deadline: game.claim_deadline,
         });
 
         Ok(())
@@ -464,6 +537,9 @@ pub struct GameCreated {
     pub creator: Pubkey,
     pub entry_fee: u64,
     pub max_players: u8,
+    pub join_deadline: i64,
+    pub roll_window: i64,
+    pub claim_window: i64,
 }
 
 #[event]
@@ -494,6 +570,7 @@ pub struct GameCompleted {
     pub winner: Pubkey,
     pub prize_amount: u64,
     pub winner_roll: u8,
+    pub claim_deadline: i64,
 }
 
 // Errors
@@ -537,4 +614,22 @@ pub enum GameError {
 
     #[msg("Game not expired yet")]
     NotExpired,
+
+    #[msg("Join window outside the allowed range")]
+    InvalidJoinWindow,
+
+    #[msg("Join deadline has passed")]
+    JoinClosed,
+
+    #[msg("Roll window outside the allowed range")]
+    InvalidRollWindow,
+
+    #[msg("Claim window outside the allowed range")]
+    InvalidClaimWindow,
+
+    #[msg("Roll deadline has passed")]
+    RollClosed,
+
+    #[msg("Slot hash is not available")]
+    SlotHashUnavailable,
 }
\ No newline at end of file
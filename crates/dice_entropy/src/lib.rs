@@ -0,0 +1,168 @@
+//! Dice roll derivation shared by the `dice_game` program and off-chain auditors.
+//!
+//! The program records the [`RollInputs`] of every roll on-chain and derives the
+//! dice by calling [`verify_roll`]. Anyone can later read those inputs back and
+//! call the same function to check that the stored dice follow from them.
+
+use solana_sha256_hasher::hashv;
+
+/// Domain separator mixed into every hashed roll.
+pub const DOMAIN: &[u8] = b"dice_game:roll";
+
+/// Tags the algorithm used to turn [`RollInputs`] into dice. Stored with every
+/// roll so old rolls stay verifiable after the derivation changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum EntropyVersion {
+    /// Both dice read straight from the slot number (`slot % 6`, `(slot >> 8) % 6`).
+    SlotModulo = 0,
+    /// SHA-256 over every input, 8 bytes of the digest per die.
+    Sha256V1 = 1,
+}
+
+impl TryFrom<u8> for EntropyVersion {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, u8> {
+        match value {
+            0 => Ok(Self::SlotModulo),
+            1 => Ok(Self::Sha256V1),
+            other => Err(other),
+        }
+    }
+}
+
+/// Everything a roll is derived from. Fields a source doesn't use are zeroed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RollInputs {
+    pub version: EntropyVersion,
+    /// Game account address.
+    pub game: [u8; 32],
+    /// Player the roll belongs to.
+    pub player: [u8; 32],
+    /// Slot the on-chain entropy was taken from.
+    pub slot: u64,
+    /// Hash of `slot` from the SlotHashes sysvar.
+    pub slot_hash: [u8; 32],
+    /// Player secret or oracle output.
+    pub seed: [u8; 32],
+    /// Distinguishes rolls that otherwise share inputs, e.g. the player's seat.
+    pub nonce: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiceRoll {
+    pub dice1: u8,
+    pub dice2: u8,
+    pub total: u8,
+}
+
+/// Derives the dice for `inputs`. Pure and deterministic, so the program and an
+/// auditor replaying the same inputs always get the same roll.
+pub fn verify_roll(inputs: &RollInputs) -> DiceRoll {
+    let (dice1, dice2) = match inputs.version {
+        EntropyVersion::SlotModulo => (
+            (inputs.slot % 6 + 1) as u8,
+            ((inputs.slot >> 8) % 6 + 1) as u8,
+        ),
+        EntropyVersion::Sha256V1 => {
+            let digest = hashv(&[
+                DOMAIN,
+                &inputs.game,
+                &inputs.player,
+                &inputs.slot.to_le_bytes(),
+                &inputs.slot_hash,
+                &inputs.seed,
+                &inputs.nonce.to_le_bytes(),
+            ])
+            .to_bytes();
+            dice_from_digest(&digest)
+        }
+    };
+
+    DiceRoll {
+        dice1,
+        dice2,
+        total: dice1 + dice2,
+    }
+}
+
+// 8 bytes per die keeps the modulo bias negligible
+fn dice_from_digest(digest: &[u8; 32]) -> (u8, u8) {
+    let a = u64::from_le_bytes(digest[0..8].try_into().unwrap());
+    let b = u64::from_le_bytes(digest[8..16].try_into().unwrap());
+    ((a % 6 + 1) as u8, (b % 6 + 1) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(version: EntropyVersion) -> RollInputs {
+        RollInputs {
+            version,
+            game: [1; 32],
+            player: [2; 32],
+            slot: 123_456_789,
+            slot_hash: [3; 32],
+            seed: [4; 32],
+            nonce: 7,
+        }
+    }
+
+    fn dice(roll: DiceRoll) -> (u8, u8, u8) {
+        (roll.dice1, roll.dice2, roll.total)
+    }
+
+    // Expected values computed independently with Python's hashlib over the same
+    // DOMAIN || game || player || slot || slot_hash || seed || nonce layout
+    #[test]
+    fn sha256_v1_known_answers() {
+        let zeroed = RollInputs {
+            game: [0; 32],
+            player: [0; 32],
+            slot: 0,
+            slot_hash: [0; 32],
+            seed: [0; 32],
+            nonce: 0,
+            ..inputs(EntropyVersion::Sha256V1)
+        };
+        assert_eq!(dice(verify_roll(&zeroed)), (1, 6, 7));
+        assert_eq!(dice(verify_roll(&inputs(EntropyVersion::Sha256V1))), (6, 3, 9));
+
+        let next_seat = RollInputs { nonce: 8, ..inputs(EntropyVersion::Sha256V1) };
+        assert_eq!(dice(verify_roll(&next_seat)), (4, 6, 10));
+    }
+
+    #[test]
+    fn slot_modulo_known_answers() {
+        for (slot, expected) in [(0, (1, 1, 2)), (773, (6, 4, 10)), (1_000_000, (5, 1, 6))] {
+            let roll = verify_roll(&RollInputs { slot, ..inputs(EntropyVersion::SlotModulo) });
+            assert_eq!(dice(roll), expected, "slot {slot}");
+        }
+    }
+
+    #[test]
+    fn dice_stay_in_range() {
+        for version in [EntropyVersion::SlotModulo, EntropyVersion::Sha256V1] {
+            for i in 0..2_000u64 {
+                let roll = verify_roll(&RollInputs {
+                    slot: i * 97,
+                    nonce: i,
+                    ..inputs(version)
+                });
+                assert!((1..=6).contains(&roll.dice1), "{version:?} dice1 {}", roll.dice1);
+                assert!((1..=6).contains(&roll.dice2), "{version:?} dice2 {}", roll.dice2);
+                assert_eq!(roll.total, roll.dice1 + roll.dice2);
+            }
+        }
+    }
+
+    #[test]
+    fn version_round_trips_through_u8() {
+        for version in [EntropyVersion::SlotModulo, EntropyVersion::Sha256V1] {
+            assert_eq!(EntropyVersion::try_from(version as u8), Ok(version));
+        }
+        assert_eq!(EntropyVersion::try_from(2), Err(2));
+    }
+}
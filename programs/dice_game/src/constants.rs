@@ -2,3 +2,17 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+// Longest a player may take to reveal after commit_roll, however long the game's roll
+// window. Kept well inside the ~512 slots the SlotHashes sysvar retains so the commit
+// slot hash is still readable.
+#[constant]
+pub const MAX_REVEAL_WINDOW_SECONDS: i64 = 120;
+
+// Upper bound on the platform fee the config admin can set (10%)
+#[constant]
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+// Upper bound on the creator rake cap the config admin can set (10%)
+#[constant]
+pub const MAX_CREATOR_RAKE_BPS: u16 = 1_000;
@@ -4,4 +4,40 @@ use anchor_lang::prelude::*;
 pub enum ErrorCode {
     #[msg("Custom error message")]
     CustomError,
+    #[msg("Account is not the SlotHashes sysvar")]
+    InvalidSlotHashes,
+    #[msg("Slot hash is not available in the SlotHashes sysvar")]
+    SlotHashUnavailable,
+    #[msg("Unknown roll entropy version")]
+    UnknownEntropyVersion,
+    #[msg("Fee is above the allowed maximum")]
+    InvalidFeeBps,
+    #[msg("Invalid entry fee range")]
+    InvalidEntryFeeRange,
+    #[msg("Creator rake cap exceeds the maximum")]
+    InvalidCreatorRakeBps,
+    #[msg("Jackpot share exceeds the whole fee")]
+    InvalidJackpotBps,
+    #[msg("Jackpot and referral shares exceed the whole fee")]
+    InvalidReferralBps,
+    #[msg("Referral account missing for a referred player")]
+    MissingReferralAccount,
+    #[msg("Players cannot refer themselves")]
+    SelfReferral,
+    #[msg("Token games need the vault, token account and token program")]
+    MissingTokenAccounts,
+    #[msg("Destination token account requires a memo; pass the memo program")]
+    MissingMemoProgram,
+    #[msg("Invalid max players")]
+    InvalidMaxPlayers,
+    #[msg("Roll-off exceeded the maximum number of rounds")]
+    TooManyRounds,
+    #[msg("Game escrow holds less than the game owes")]
+    EscrowShortfall,
+    #[msg("Deadline windows must be positive with minimums no higher than maximums")]
+    InvalidDeadlineBounds,
+    #[msg("Stale game timeout must be longer than the longest roll window")]
+    InvalidStaleGameTimeout,
+    #[msg("Refund accounts must match the game's players, in seat order")]
+    RefundAccountMismatch,
 }
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::error::ErrorCode;
+use crate::state::GameAccount;
+
+// Lamport stakes, sponsorships and side bets sit in an escrow at ["escrow", game]: a
+// system account with no data, so funds only leave it through system transfers signed
+// with its seeds, and the game account never holds more than its own rent.
+//
+// The escrow keeps the rent-exempt minimum of an empty account as a reserve, paid by
+// create_game and returned by close_game. Invariant: escrow balance == reserve +
+// GameAccount::escrow_liabilities(). Every transfer in or out is paired with the
+// bookkeeping change that accounts for it, and handlers end with check(). Only lamports
+// sent to the escrow from outside can push it above its liabilities; close_game sweeps
+// those to the treasury.
+pub struct Escrow<'a, 'info> {
+    pub account: &'a SystemAccount<'info>,
+    pub system_program: &'a Program<'info, System>,
+}
+
+impl<'a, 'info> Escrow<'a, 'info> {
+    pub fn reserve() -> Result<u64> {
+        Ok(Rent::get()?.minimum_balance(0))
+    }
+
+    // Moves `amount` into the escrow from a signing wallet
+    pub fn deposit(&self, from: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: from.clone(),
+                    to: self.account.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    // Moves `amount` out of the escrow, signed with the escrow's seeds
+    pub fn withdraw(
+        &self,
+        game: &Account<'info, GameAccount>,
+        to: &AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let game_key = game.key();
+        let seeds: &[&[u8]] = &[b"escrow", game_key.as_ref(), &[game.escrow_bump]];
+
+        transfer(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.account.to_account_info(),
+                    to: to.clone(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )
+    }
+
+    // The escrow must still cover its reserve and everything the game owes
+    pub fn check(&self, game: &GameAccount) -> Result<()> {
+        let required = Self::reserve()? + game.escrow_liabilities();
+        require_gte!(self.account.lamports(), required, ErrorCode::EscrowShortfall);
+        Ok(())
+    }
+}
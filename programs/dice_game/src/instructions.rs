@@ -1,17 +1,55 @@
+#![allow(ambiguous_glob_reexports)]
+
 pub mod initialize;
+pub mod update_config;
 pub mod create_game;
 pub mod join_game;
 pub mod start_game;
 pub mod roll_dice;
+pub mod commit_roll;
+pub mod reveal_roll;
+pub mod reveal_seed;
+pub mod fulfill_randomness;
+pub mod close_randomness_request;
 pub mod finalize_game;
+pub mod forfeit_idle_players;
 pub mod emergency_withdraw;
 pub mod claim_prize;
+pub mod withdraw_treasury;
+pub mod claim_referral_rewards;
+pub mod sponsor_game;
+pub mod refund_sponsorship;
+pub mod place_side_bet;
+pub mod settle_side_bet;
+pub mod collect_token_fees;
+pub mod close_game;
+pub mod abort_stale_game;
+pub mod cancel_game;
+pub mod leave_game;
 
 pub use initialize::*;
+pub use update_config::*;
 pub use create_game::*;
 pub use join_game::*;
 pub use start_game::*;
 pub use roll_dice::*;
+pub use commit_roll::*;
+pub use reveal_roll::*;
+pub use reveal_seed::*;
+pub use fulfill_randomness::*;
+pub use close_randomness_request::*;
 pub use finalize_game::*;
+pub use forfeit_idle_players::*;
 pub use emergency_withdraw::*;
 pub use claim_prize::*;
+pub use withdraw_treasury::*;
+pub use claim_referral_rewards::*;
+pub use sponsor_game::*;
+pub use refund_sponsorship::*;
+pub use place_side_bet::*;
+pub use settle_side_bet::*;
+pub use collect_token_fees::*;
+pub use close_game::*;
+pub use abort_stale_game::*;
+pub use cancel_game::*;
+pub use leave_game::*;
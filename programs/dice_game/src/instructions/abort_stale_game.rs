@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_spl::memo::Memo;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::escrow::Escrow;
+use crate::refund::refund_players;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct AbortStaleGame<'info> {
+    #[account(
+        mut,
+        constraint = game_account.status == GameStatus::Playing @ CustomError::GameNotPlaying,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    // Token games only
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", game_account.key().as_ref()],
+        bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // Only needed when a player's token account requires incoming memos
+    pub memo_program: Option<Program<'info, Memo>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Last resort for a game that started but never finished: once the configured timeout
+// has passed both since it started and since the current round's roll deadline, anyone
+// can cancel it and refund every player. A roll-off that is still being played keeps
+// pushing the roll deadline out, so it can't be aborted. The players' wallets (or token
+// accounts) are passed as remaining accounts in seat order. Sponsorships and side bets
+// are then refunded through their own instructions.
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, AbortStaleGame<'info>>) -> Result<()> {
+    let clock = Clock::get()?;
+    let game = &ctx.accounts.game_account;
+    let timeout = ctx.accounts.config.stale_game_timeout;
+    let started_at = game.started_at.unwrap_or_default();
+    require!(
+        clock.unix_timestamp > started_at + timeout
+            && clock.unix_timestamp > game.roll_deadline + timeout,
+        CustomError::NotStale
+    );
+
+    let refunded = refund_players(
+        &mut ctx.accounts.game_account,
+        &Escrow {
+            account: &ctx.accounts.escrow,
+            system_program: &ctx.accounts.system_program,
+        },
+        &ctx.accounts.mint,
+        &mut ctx.accounts.vault,
+        &ctx.accounts.token_program,
+        &ctx.accounts.memo_program,
+        ctx.remaining_accounts,
+    )?;
+
+    let game = &mut ctx.accounts.game_account;
+    game.status = GameStatus::Cancelled;
+    game.completed_at = Some(clock.unix_timestamp);
+
+    emit!(GameAborted {
+        game_id: game.game_id,
+        players: game.players.clone(),
+        refunded,
+        round: game.round,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct GameAborted {
+    pub game_id: u64,
+    pub players: Vec<Pubkey>,
+    pub refunded: u64,
+    pub round: u8,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Game is not in playing state")]
+    GameNotPlaying,
+    #[msg("Game has not been stale long enough to abort")]
+    NotStale,
+}
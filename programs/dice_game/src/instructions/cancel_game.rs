@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use anchor_spl::memo::Memo;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::escrow::Escrow;
+use crate::refund::refund_players;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CancelGame<'info> {
+    #[account(
+        mut,
+        constraint = game_account.status == GameStatus::Waiting @ CustomError::GameNotWaiting,
+        has_one = creator @ CustomError::NotCreator,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    // Token games only
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", game_account.key().as_ref()],
+        bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // Only needed when a player's token account requires incoming memos
+    pub memo_program: Option<Program<'info, Memo>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// The creator can call a game off any time before it starts. Every player is refunded
+// in this transaction: their wallets (or token accounts) go in remaining accounts, in
+// the same order as `players`.
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, CancelGame<'info>>) -> Result<()> {
+    let refunded = refund_players(
+        &mut ctx.accounts.game_account,
+        &Escrow {
+            account: &ctx.accounts.escrow,
+            system_program: &ctx.accounts.system_program,
+        },
+        &ctx.accounts.mint,
+        &mut ctx.accounts.vault,
+        &ctx.accounts.token_program,
+        &ctx.accounts.memo_program,
+        ctx.remaining_accounts,
+    )?;
+
+    let clock = Clock::get()?;
+    let game = &mut ctx.accounts.game_account;
+    game.status = GameStatus::Cancelled;
+    game.completed_at = Some(clock.unix_timestamp);
+
+    emit!(GameCancelled {
+        game_id: game.game_id,
+        players: game.players.clone(),
+        refunded,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct GameCancelled {
+    pub game_id: u64,
+    pub players: Vec<Pubkey>,
+    pub refunded: u64,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Game is not waiting for players")]
+    GameNotWaiting,
+    #[msg("Only the game creator can cancel it")]
+    NotCreator,
+}
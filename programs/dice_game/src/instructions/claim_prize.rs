@@ -1,45 +1,113 @@
 use anchor_lang::prelude::*;
+use anchor_spl::memo::Memo;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::escrow::Escrow;
 use crate::state::*;
+use crate::token::TokenAccounts;
 
 #[derive(Accounts)]
 pub struct ClaimPrize<'info> {
     #[account(
         mut,
         constraint = game_account.status == GameStatus::Completed @ CustomError::GameNotCompleted,
-        constraint = game_account.winner == Some(winner.key()) @ CustomError::NotWinner,
+        constraint = game_account.payouts.iter().any(|p| p.player == winner.key())
+            || (game_account.creator == winner.key() && game_account.creator_rake > 0)
+            @ CustomError::NotWinner,
     )]
     pub game_account: Account<'info, GameAccount>,
 
     #[account(mut)]
     pub winner: Signer<'info>,
 
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    // Token games only
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", game_account.key().as_ref()],
+        bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub winner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // Only needed when the destination token account requires incoming memos
+    pub memo_program: Option<Program<'info, Memo>>,
+
     pub system_program: Program<'info, System>,
 }
 
 pub fn handler(ctx: Context<ClaimPrize>) -> Result<()> {
-    // Check if prize already claimed
-    require!(!ctx.accounts.game_account.prize_claimed, CustomError::PrizeAlreadyClaimed);
+    let winner_key = ctx.accounts.winner.key();
+    let game = &mut ctx.accounts.game_account;
 
-    // Calculate prize (minus 2.5% fee)
-    let total_pool = ctx.accounts.game_account.total_pool;
-    let fee = total_pool * 25 / 1000; // 2.5%
-    let prize = total_pool - fee;
+    // Unclaimed shares go to the treasury when the game is closed after the deadline
+    let clock = Clock::get()?;
+    require!(clock.unix_timestamp <= game.claim_deadline, CustomError::ClaimWindowClosed);
 
-    // Transfer prize from game account to winner
-    **ctx.accounts.game_account.to_account_info().lamports.borrow_mut() -= prize;
-    **ctx.accounts.winner.to_account_info().lamports.borrow_mut() += prize;
+    // Each ranked player's share was fixed at settlement; the fee and dust already went to the treasury
+    let payout_index = game.payouts.iter().position(|p| p.player == winner_key);
+    let prize = payout_index
+        .map(|i| game.payouts[i])
+        .filter(|p| !p.claimed);
 
-    // Get game_id before mutably borrowing
-    let game_id = ctx.accounts.game_account.game_id;
+    // The creator collects their rake here too, alongside any prize of their own
+    let rake_due = game.creator == winner_key && game.creator_rake > 0 && !game.rake_claimed;
 
-    // Mark prize as claimed
-    let game = &mut ctx.accounts.game_account;
-    game.prize_claimed = true;
+    // Check if prize already claimed
+    require!(prize.is_some() || rake_due, CustomError::PrizeAlreadyClaimed);
+
+    let prize_amount = prize.map_or(0, |p| p.amount);
+    let jackpot = prize.map_or(0, |p| p.jackpot);
+    let creator_rake = if rake_due { game.creator_rake } else { 0 };
+    let amount = prize_amount + jackpot + creator_rake;
+
+    // Transfer prize from the escrow, or the vault in token games, to the winner
+    let escrow = Escrow {
+        account: &ctx.accounts.escrow,
+        system_program: &ctx.accounts.system_program,
+    };
+    if game.mint.is_some() {
+        TokenAccounts::require(
+            &ctx.accounts.mint,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.winner_token_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.memo_program,
+        )?
+        .withdraw(game, amount)?;
+    } else {
+        escrow.withdraw(game, &ctx.accounts.winner.to_account_info(), amount)?;
+    }
+
+    // Mark the shares as claimed, and the game once every share is
+    if let (Some(i), Some(_)) = (payout_index, prize) {
+        game.payouts[i].claimed = true;
+    }
+    if rake_due {
+        game.rake_claimed = true;
+    }
+    game.prize_claimed = game.all_claimed();
+    escrow.check(game)?;
 
     emit!(PrizeClaimed {
-        game_id,
-        winner: ctx.accounts.winner.key(),
-        prize_amount: prize,
+        game_id: game.game_id,
+        winner: winner_key,
+        prize_amount,
+        rank: prize.map_or(0, |p| p.rank),
+        jackpot,
+        creator_rake,
+        platform_fee: game.fee_collected,
     });
 
     Ok(())
@@ -50,14 +118,21 @@ pub struct PrizeClaimed {
     pub game_id: u64,
     pub winner: Pubkey,
     pub prize_amount: u64,
+    // 0 when the claim paid no ranked share
+    pub rank: u8,
+    pub jackpot: u64,
+    pub creator_rake: u64,
+    pub platform_fee: u64,
 }
 
 #[error_code]
 pub enum CustomError {
     #[msg("Game is not completed")]
     GameNotCompleted,
-    #[msg("You are not the winner")]
+    #[msg("You have no payout in this game")]
     NotWinner,
     #[msg("Prize already claimed")]
     PrizeAlreadyClaimed,
-}
\ No newline at end of file
+    #[msg("Claim deadline has passed")]
+    ClaimWindowClosed,
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"referral", referral.player.as_ref()],
+        bump = referral.bump,
+        has_one = referrer @ CustomError::NotReferrer,
+    )]
+    pub referral: Account<'info, Referral>,
+
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+    let referral = &mut ctx.accounts.referral;
+    let amount = referral.unclaimed();
+    require!(amount > 0, CustomError::NothingToClaim);
+
+    **referral.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **ctx.accounts.referrer.to_account_info().try_borrow_mut_lamports()? += amount;
+
+    referral.claimed += amount;
+
+    emit!(ReferralRewardsClaimed {
+        player: referral.player,
+        referrer: referral.referrer,
+        amount,
+        total_claimed: referral.claimed,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ReferralRewardsClaimed {
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+    pub amount: u64,
+    pub total_claimed: u64,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Signer is not this player's referrer")]
+    NotReferrer,
+    #[msg("No referral rewards to claim")]
+    NothingToClaim,
+}
@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+use anchor_spl::memo::Memo;
+use anchor_spl::token_interface::{
+    close_account, CloseAccount, Mint, TokenAccount, TokenInterface,
+};
+use crate::escrow::Escrow;
+use crate::state::*;
+use crate::token::TokenAccounts;
+
+#[derive(Accounts)]
+pub struct CloseGame<'info> {
+    // Closing frees the ["game", game_id] address, so the game_id can be used again
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = creator @ CustomError::NotCreator,
+        has_one = rent_payer @ CustomError::WrongRentPayer,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    pub creator: Signer<'info>,
+
+    // Gets back the rent it paid at create_game, even after handing the creator role on
+    #[account(mut)]
+    pub rent_payer: SystemAccount<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    // Emptied: anything above its rent reserve goes to the treasury, the reserve to the
+    // rent payer
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    // Token games only; the vault is emptied into the treasury wallet's token account
+    // and closed back to the rent payer. The mint takes the vault's withheld transfer fees
+    #[account(mut)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", game_account.key().as_ref()],
+        bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == config.treasury @ CustomError::WrongDestination,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // Only needed when the treasury token account requires incoming memos
+    pub memo_program: Option<Program<'info, Memo>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CloseGame>) -> Result<()> {
+    let game = &ctx.accounts.game_account;
+
+    // Nothing may still be owed out of the game: payouts, rake, fees, side bets and
+    // sponsorships must all be settled first. Payouts left unclaimed past the claim
+    // deadline are forfeited to the treasury with the sweep below
+    let clock = Clock::get()?;
+    match game.status {
+        GameStatus::Completed => {
+            require!(
+                game.all_claimed() || clock.unix_timestamp > game.claim_deadline,
+                CustomError::UnsettledPayouts
+            );
+            require!(
+                game.mint.is_none() || game.token_fees_collected,
+                CustomError::UnsettledFees
+            );
+        }
+        GameStatus::Cancelled => {
+            require!(
+                game.total_pool == 0 && game.sponsor_pool == 0,
+                CustomError::UnsettledRefunds
+            );
+        }
+        _ => return err!(CustomError::GameNotFinished),
+    }
+    require!(game.side_pool == 0, CustomError::UnsettledSideBets);
+
+    // Leftover vault tokens go to the treasury wallet, then the vault's rent to the rent payer
+    let mut swept_tokens = 0;
+    if game.mint.is_some() {
+        let accounts = TokenAccounts::require(
+            &ctx.accounts.mint,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.treasury_token_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.memo_program,
+        )?;
+
+        swept_tokens = accounts.vault.amount;
+        if swept_tokens > 0 {
+            accounts.withdraw(game, swept_tokens)?;
+        }
+        accounts.harvest_withheld_fees()?;
+
+        let game_id = game.game_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"game", game_id.as_ref(), &[game.bump]];
+        close_account(CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: accounts.vault.to_account_info(),
+                destination: ctx.accounts.rent_payer.to_account_info(),
+                authority: game.to_account_info(),
+            },
+            &[seeds],
+        ))?;
+    }
+
+    // What the escrow holds beyond its reserve is expired payouts, side bet rounding dust
+    // or lamports sent to it directly. The reserve and the game account's rent return to
+    // the rent payer
+    let escrow = Escrow {
+        account: &ctx.accounts.escrow,
+        system_program: &ctx.accounts.system_program,
+    };
+    let reserve = escrow.account.lamports().min(Escrow::reserve()?);
+    let swept = escrow.account.lamports() - reserve;
+
+    escrow.withdraw(game, &ctx.accounts.treasury.to_account_info(), swept)?;
+    escrow.withdraw(game, &ctx.accounts.rent_payer.to_account_info(), reserve)?;
+    ctx.accounts.treasury.total_accrued += swept;
+
+    emit!(GameClosed {
+        game_id: game.game_id,
+        creator: ctx.accounts.creator.key(),
+        rent_payer: ctx.accounts.rent_payer.key(),
+        swept,
+        swept_tokens,
+        rent_returned: ctx.accounts.game_account.get_lamports() + reserve,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct GameClosed {
+    pub game_id: u64,
+    pub creator: Pubkey,
+    pub rent_payer: Pubkey,
+    pub swept: u64,
+    pub swept_tokens: u64,
+    pub rent_returned: u64,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Only the game creator can close it")]
+    NotCreator,
+    #[msg("Rent payer does not match the game")]
+    WrongRentPayer,
+    #[msg("Game is neither completed nor cancelled")]
+    GameNotFinished,
+    #[msg("Payouts or creator rake unclaimed and the claim deadline hasn't passed")]
+    UnsettledPayouts,
+    #[msg("Token fees not collected yet")]
+    UnsettledFees,
+    #[msg("Entry fees or sponsorships not refunded yet")]
+    UnsettledRefunds,
+    #[msg("Side bets not settled yet")]
+    UnsettledSideBets,
+    #[msg("Token account is not owned by the configured treasury wallet")]
+    WrongDestination,
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CloseRandomnessRequest<'info> {
+    #[account(
+        mut,
+        close = player,
+        seeds = [
+            b"randomness_request",
+            randomness_request.game.as_ref(),
+            player.key().as_ref(),
+            &[randomness_request.round],
+        ],
+        bump = randomness_request.bump,
+        has_one = player,
+    )]
+    pub randomness_request: Account<'info, RandomnessRequest>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// CHECK: read only to tell whether the request can still be fulfilled; it may have been
+    /// closed or reused under the same game_id since
+    #[account(address = randomness_request.game)]
+    pub game_account: UncheckedAccount<'info>,
+}
+
+// Fulfilled requests close themselves. This returns the rent of one the oracle never
+// answered, once it no longer can: the round is over, the game finished or was closed.
+pub fn handler(ctx: Context<CloseRandomnessRequest>) -> Result<()> {
+    let request = &ctx.accounts.randomness_request;
+    let info = ctx.accounts.game_account.to_account_info();
+
+    if info.owner == &crate::ID && !info.data_is_empty() {
+        let game = GameAccount::try_deserialize(&mut &info.data.borrow()[..])?;
+        let pending = game.nonce == request.game_nonce
+            && game.status == GameStatus::Playing
+            && game.round == request.round;
+        require!(!pending, CustomError::RequestPending);
+    }
+
+    Ok(())
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Randomness request can still be fulfilled")]
+    RequestPending,
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_spl::memo::Memo;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::token::TokenAccounts;
+
+#[derive(Accounts)]
+pub struct CollectTokenFees<'info> {
+    #[account(
+        mut,
+        constraint = game_account.status == GameStatus::Completed @ CustomError::GameNotCompleted,
+        constraint = game_account.mint.is_some() @ CustomError::NotTokenGame,
+        constraint = !game_account.token_fees_collected @ CustomError::AlreadyCollected,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(address = game_account.mint.unwrap_or_default() @ CustomError::WrongMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", game_account.key().as_ref()],
+        bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    // Token account of the config's treasury wallet for the game's mint
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == config.treasury @ CustomError::WrongDestination,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    // Only needed when the treasury token account requires incoming memos
+    pub memo_program: Option<Program<'info, Memo>>,
+}
+
+// Token games leave their platform fee and split dust in the vault at settlement;
+// anyone can forward them to the treasury wallet once.
+pub fn handler(ctx: Context<CollectTokenFees>) -> Result<()> {
+    let game = &ctx.accounts.game_account;
+    let amount = game.fee_collected + game.payout_dust;
+
+    if amount > 0 {
+        TokenAccounts {
+            mint: &ctx.accounts.mint,
+            vault: &mut ctx.accounts.vault,
+            user: &ctx.accounts.treasury_token_account,
+            token_program: &ctx.accounts.token_program,
+            memo_program: ctx.accounts.memo_program.as_ref(),
+        }
+        .withdraw(game, amount)?;
+    }
+
+    let game = &mut ctx.accounts.game_account;
+    game.token_fees_collected = true;
+
+    emit!(TokenFeesCollected {
+        game_id: game.game_id,
+        mint: ctx.accounts.mint.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TokenFeesCollected {
+    pub game_id: u64,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Game is not completed")]
+    GameNotCompleted,
+    #[msg("Game does not use a token")]
+    NotTokenGame,
+    #[msg("Token fees already collected")]
+    AlreadyCollected,
+    #[msg("Mint does not match the game")]
+    WrongMint,
+    #[msg("Token account is not owned by the configured treasury wallet")]
+    WrongDestination,
+}
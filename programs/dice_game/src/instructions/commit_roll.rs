@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use crate::constants::MAX_REVEAL_WINDOW_SECONDS;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CommitRoll<'info> {
+    #[account(
+        mut,
+        constraint = game_account.status == GameStatus::Playing @ CustomError::GameNotPlaying,
+        constraint = game_account.randomness == RandomnessSource::CommitReveal @ CustomError::NotCommitReveal,
+        constraint = game_account.players.contains(&player.key()) @ CustomError::PlayerNotInGame,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    pub player: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<CommitRoll>, commitment: [u8; 32]) -> Result<()> {
+    let game = &mut ctx.accounts.game_account;
+    let clock = Clock::get()?;
+
+    let player_index = game.players
+        .iter()
+        .position(|p| p == &ctx.accounts.player.key())
+        .ok_or(CustomError::PlayerNotInGame)?;
+
+    require!(game.in_round[player_index], CustomError::NotInRound);
+    require!(game.rolls[player_index].is_none(), CustomError::AlreadyRolled);
+    require!(game.commitments[player_index].is_none(), CustomError::AlreadyCommitted);
+    require!(clock.unix_timestamp <= game.roll_deadline, CustomError::RollDeadlinePassed);
+
+    // The roll is later mixed with the hash of this slot, which is not known yet. The
+    // reveal is due by the round's roll deadline, sooner if the slot hash would expire first
+    let reveal_deadline = game.roll_deadline.min(clock.unix_timestamp + MAX_REVEAL_WINDOW_SECONDS);
+    game.commitments[player_index] = Some(RollCommitment {
+        commitment,
+        committed_slot: clock.slot,
+        reveal_deadline,
+    });
+
+    emit!(RollCommitted {
+        game_id: game.game_id,
+        player: ctx.accounts.player.key(),
+        committed_slot: clock.slot,
+        reveal_deadline,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct RollCommitted {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub committed_slot: u64,
+    pub reveal_deadline: i64,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Game is not in playing state")]
+    GameNotPlaying,
+    #[msg("Game does not use commit-reveal rolls")]
+    NotCommitReveal,
+    #[msg("Player not in this game")]
+    PlayerNotInGame,
+    #[msg("Player is not in the current roll-off round")]
+    NotInRound,
+    #[msg("Player already rolled dice")]
+    AlreadyRolled,
+    #[msg("Player already committed a roll")]
+    AlreadyCommitted,
+    #[msg("Roll deadline for this round has passed")]
+    RollDeadlinePassed,
+}
@@ -1,5 +1,9 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::escrow::Escrow;
+use crate::referral::link_referrer;
 use crate::state::*;
+use crate::token::TokenAccounts;
 
 #[derive(Accounts)]
 #[instruction(game_id: u64, entry_fee: u64, max_players: u8)]
@@ -13,51 +17,220 @@ pub struct CreateGame<'info> {
     )]
     pub game_account: Account<'info, GameAccount>,
 
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // Hands out the game's nonce
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
     #[account(mut)]
     pub creator: Signer<'info>,
 
+    // Holds the game's lamports; funded here with its rent reserve
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    /// CHECK: the creator's referral PDA, read if it exists and opened when naming a referrer
+    #[account(
+        mut,
+        seeds = [b"referral", creator.key().as_ref()],
+        bump
+    )]
+    pub referral: UncheckedAccount<'info>,
+
+    // Token games only: entry fees are paid in this mint into the game's vault
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"vault", game_account.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = game_account,
+        token::token_program = token_program,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub creator_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    // SPL Token or Token-2022
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<CreateGame>, game_id: u64, entry_fee: u64, max_players: u8) -> Result<()> {
-    require!(max_players >= 2 && max_players <= 6, CustomError::InvalidMaxPlayers);
-    require!(entry_fee > 0, CustomError::InvalidEntryFee);
+#[allow(clippy::too_many_arguments)]
+pub fn handler(
+    ctx: Context<CreateGame>,
+    game_id: u64,
+    entry_fee: u64,
+    max_players: u8,
+    min_players: u8,
+    randomness: RandomnessSource,
+    seed_commitment: Option<[u8; 32]>,
+    tie_break: TieBreak,
+    payout_tiers: Vec<u16>,
+    creator_rake_bps: u16,
+    referrer: Option<Pubkey>,
+    deadlines: Deadlines,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!((2..=config.max_players).contains(&max_players), CustomError::InvalidMaxPlayers);
+    require!((2..=max_players).contains(&min_players), CustomError::InvalidMinPlayers);
+    // An entry fee of zero makes the game a freeroll funded by sponsor_game. The config's
+    // range is in lamports, so token games only need a non-zero fee
+    let mint = ctx.accounts.mint.as_ref().map(|m| m.key());
+    require!(
+        match mint {
+            Some(_) => entry_fee > 0,
+            None => entry_fee == 0 || (config.min_entry_fee..=config.max_entry_fee).contains(&entry_fee),
+        },
+        CustomError::InvalidEntryFee
+    );
+    require!(
+        seed_commitment.is_some() == (randomness == RandomnessSource::Collaborative),
+        CustomError::InvalidSeedCommitment
+    );
+    require!(
+        GameAccount::valid_payout_tiers(&payout_tiers, max_players),
+        CustomError::InvalidPayoutTiers
+    );
+    require!(creator_rake_bps <= config.max_creator_rake_bps, CustomError::CreatorRakeTooHigh);
+    require!(
+        deadlines.within(&config.min_deadlines, &config.max_deadlines),
+        CustomError::DeadlineOutOfBounds
+    );
 
+    let fee_bps = config.fee_bps;
+    let jackpot_bps = config.jackpot_bps;
+    let jackpot_trigger = config.jackpot_trigger;
+    let referral_bps = config.referral_bps;
+    let creator_referrer = link_referrer(
+        &ctx.accounts.referral,
+        ctx.bumps.referral,
+        &ctx.accounts.creator,
+        &ctx.accounts.system_program,
+        referrer,
+    )?;
+    let treasury = &mut ctx.accounts.treasury;
+    let nonce = treasury.games_created;
+    treasury.games_created += 1;
     let game = &mut ctx.accounts.game_account;
     let clock = Clock::get()?;
 
     game.game_id = game_id;
     game.creator = ctx.accounts.creator.key();
+    game.rent_payer = ctx.accounts.creator.key();
     game.entry_fee = entry_fee;
     game.max_players = max_players;
+    game.min_players = min_players;
     game.current_players = 1;
     game.total_pool = entry_fee;
     game.status = GameStatus::Waiting;
     game.created_at = clock.unix_timestamp;
+    game.nonce = nonce;
     game.started_at = None;
     game.completed_at = None;
     game.players = vec![ctx.accounts.creator.key()];
     game.rolls = vec![None; max_players as usize];
+    game.roll_history = Vec::new();
     game.winner = None;
     game.prize_claimed = false;
+    game.randomness = randomness;
+    game.commitments = vec![None; max_players as usize];
+    game.commitments[0] = seed_commitment.map(|commitment| RollCommitment {
+        commitment,
+        committed_slot: clock.slot,
+        reveal_deadline: 0, // set when the game starts
+    });
+    game.seeds = vec![None; max_players as usize];
+    game.fee_bps = fee_bps;
+    game.fee_collected = 0;
+    game.payouts = Vec::new();
+    game.payout_dust = 0;
+    game.tie_break = tie_break;
+    game.round = 0;
+    game.in_round = vec![true; max_players as usize];
+    game.deadlines = deadlines;
+    game.join_deadline = clock.unix_timestamp + deadlines.join_window;
+    game.roll_deadline = 0; // set when each round opens
+    game.claim_deadline = 0; // set at settlement
+    game.forfeited = vec![false; max_players as usize];
+    game.payout_tiers = payout_tiers.clone();
+    game.creator_rake_bps = creator_rake_bps;
+    game.creator_rake = 0;
+    game.rake_claimed = false;
+    game.jackpot_bps = jackpot_bps;
+    game.jackpot_trigger = jackpot_trigger;
+    game.referrers = vec![None; max_players as usize];
+    game.referrers[0] = creator_referrer;
+    game.referral_bps = referral_bps;
+    game.sponsor_pool = 0;
+    game.side_pool = 0;
+    game.side_stakes = vec![0; max_players as usize];
+    game.bump = ctx.bumps.game_account;
+    game.mint = mint;
+    game.token_fees_collected = false;
+    game.deposits = vec![0; max_players as usize];
+    game.side_held = 0;
+    game.seat_ids = vec![0; max_players as usize];
+    game.seat_ids[0] = 1;
+    game.next_seat_id = 2;
+    game.escrow_bump = ctx.bumps.escrow;
+
+    let escrow = Escrow {
+        account: &ctx.accounts.escrow,
+        system_program: &ctx.accounts.system_program,
+    };
+    let creator = ctx.accounts.creator.to_account_info();
+
+    // Top the escrow up to its rent reserve; it may still hold some from earlier
+    // transfers to the address
+    let reserve = Escrow::reserve()?.saturating_sub(escrow.account.lamports());
+    escrow.deposit(&creator, reserve)?;
 
-    // Transfer entry fee from creator to game account
-    anchor_lang::system_program::transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            anchor_lang::system_program::Transfer {
-                from: ctx.accounts.creator.to_account_info(),
-                to: ctx.accounts.game_account.to_account_info(),
-            }
-        ),
+    let deposited = if mint.is_some() {
+        // Transfer entry fee from creator's token account to the vault
+        TokenAccounts::require(
+            &ctx.accounts.mint,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.creator_token_account,
+            &ctx.accounts.token_program,
+            &None,
+        )?
+        .deposit(&ctx.accounts.creator, entry_fee)?
+    } else {
+        // Transfer entry fee from creator to the escrow
+        escrow.deposit(&creator, entry_fee)?;
         entry_fee
-    )?;
+    };
+
+    // The pool counts what arrived, which is less than the entry fee under a transfer fee
+    let game = &mut ctx.accounts.game_account;
+    game.total_pool = deposited;
+    game.deposits[0] = deposited;
+    escrow.check(game)?;
 
     emit!(GameCreated {
         game_id,
         creator: ctx.accounts.creator.key(),
         entry_fee,
         max_players,
+        min_players,
+        randomness,
+        tie_break,
+        payout_tiers,
+        creator_rake_bps,
+        mint,
+        deadlines,
+        join_deadline: ctx.accounts.game_account.join_deadline,
     });
 
     Ok(())
@@ -69,12 +242,30 @@ pub struct GameCreated {
     pub creator: Pubkey,
     pub entry_fee: u64,
     pub max_players: u8,
+    pub min_players: u8,
+    pub randomness: RandomnessSource,
+    pub tie_break: TieBreak,
+    pub payout_tiers: Vec<u16>,
+    pub creator_rake_bps: u16,
+    pub mint: Option<Pubkey>,
+    pub deadlines: Deadlines,
+    pub join_deadline: i64,
 }
 
 #[error_code]
 pub enum CustomError {
-    #[msg("Invalid max players (must be 2-6)")]
+    #[msg("Invalid max players (must be 2 to the configured maximum)")]
     InvalidMaxPlayers,
-    #[msg("Invalid entry fee")]
+    #[msg("Invalid min players (must be 2 to max players)")]
+    InvalidMinPlayers,
+    #[msg("Entry fee outside the configured range, or zero in a token game")]
     InvalidEntryFee,
+    #[msg("Seed commitment is required for collaborative games and only allowed there")]
+    InvalidSeedCommitment,
+    #[msg("Payout tiers must be non-increasing, at most max players long and sum to 10000 bps")]
+    InvalidPayoutTiers,
+    #[msg("Creator rake exceeds the configured maximum")]
+    CreatorRakeTooHigh,
+    #[msg("Deadline windows outside the configured bounds")]
+    DeadlineOutOfBounds,
 }
\ No newline at end of file
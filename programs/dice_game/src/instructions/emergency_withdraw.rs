@@ -1,5 +1,9 @@
 use anchor_lang::prelude::*;
+use anchor_spl::memo::Memo;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::escrow::Escrow;
 use crate::state::*;
+use crate::token::TokenAccounts;
 
 #[derive(Accounts)]
 pub struct EmergencyWithdraw<'info> {
@@ -13,6 +17,31 @@ pub struct EmergencyWithdraw<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
 
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    // Token games only
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", game_account.key().as_ref()],
+        bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // Only needed when the destination token account requires incoming memos
+    pub memo_program: Option<Program<'info, Memo>>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -20,34 +49,43 @@ pub fn handler(ctx: Context<EmergencyWithdraw>) -> Result<()> {
     let clock = Clock::get()?;
 
     // Get necessary data before mutable borrow
-    let (expired, player_index, entry_fee) = {
+    let (expired, startable, player_index, deposit) = {
         let game = &ctx.accounts.game_account;
-        let expired = clock.unix_timestamp - game.created_at > 86400;
+        let expired = clock.unix_timestamp > game.join_deadline;
+        let startable = game.current_players >= game.min_players;
         let player_index = game.players
             .iter()
             .position(|p| p == &ctx.accounts.player.key())
             .ok_or(CustomError::PlayerNotInGame)?;
-        (expired, player_index, game.entry_fee)
+        (expired, startable, player_index, game.deposits[player_index])
     };
 
-    // Check if game has expired (24 hours without starting)
+    // Check the join deadline passed without enough players to start
     require!(expired, CustomError::NotExpired);
+    require!(!startable, CustomError::GameCanStart);
 
-    // Return entry fee to player
-    **ctx.accounts.game_account.to_account_info().lamports.borrow_mut() -= entry_fee;
-    **ctx.accounts.player.to_account_info().lamports.borrow_mut() += entry_fee;
+    // Return the player's deposit
+    let escrow = Escrow {
+        account: &ctx.accounts.escrow,
+        system_program: &ctx.accounts.system_program,
+    };
+    if ctx.accounts.game_account.mint.is_some() {
+        TokenAccounts::require(
+            &ctx.accounts.mint,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.player_token_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.memo_program,
+        )?
+        .withdraw(&ctx.accounts.game_account, deposit)?;
+    } else {
+        escrow.withdraw(&ctx.accounts.game_account, &ctx.accounts.player.to_account_info(), deposit)?;
+    }
 
-    // Remove player from game
+    // Remove player from game; the game is cancelled if no players are left
     let game = &mut ctx.accounts.game_account;
-    game.players.remove(player_index);
-    game.current_players -= 1;
-    game.total_pool -= entry_fee;
-
-    // If no players left, cancel the game
-    if game.current_players == 0 {
-        game.status = GameStatus::Cancelled;
-        game.completed_at = Some(clock.unix_timestamp);
-    }
+    game.leave(player_index, clock.unix_timestamp);
+    escrow.check(game)?;
 
     Ok(())
 }
@@ -60,4 +98,6 @@ pub enum CustomError {
     PlayerNotInGame,
     #[msg("Game not expired yet")]
     NotExpired,
+    #[msg("Enough players joined; start the game instead")]
+    GameCanStart,
 }
\ No newline at end of file
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use crate::instructions::reveal_seed::roll_game_seed;
+use crate::escrow::Escrow;
+use crate::settlement::settle_game;
 use crate::state::*;
 
 #[derive(Accounts)]
@@ -9,49 +12,52 @@ pub struct FinalizeGame<'info> {
     )]
     pub game_account: Account<'info, GameAccount>,
 
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut, seeds = [b"jackpot"], bump = jackpot.bump)]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<FinalizeGame>) -> Result<()> {
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FinalizeGame<'info>>) -> Result<()> {
+    let game_key = ctx.accounts.game_account.key();
     let game = &mut ctx.accounts.game_account;
     let clock = Clock::get()?;
 
-    // Check if all players have rolled
-    let all_rolled = game.players
-        .iter()
-        .enumerate()
-        .take(game.current_players as usize)
-        .all(|(i, _)| game.rolls[i].is_some());
-
-    require!(all_rolled, CustomError::WaitingForRolls);
-
-    // Find winner
-    let mut winner_index = 0;
-    let mut highest_roll = 0u8;
-
-    for (i, roll_opt) in game.rolls.iter().enumerate().take(game.current_players as usize) {
-        if let Some(roll) = roll_opt {
-            if roll.total > highest_roll {
-                highest_roll = roll.total;
-                winner_index = i;
-            }
-        }
+    // Collaborative games whose seeds weren't all in by the roll deadline roll from the
+    // seeds that were revealed
+    if game.randomness == RandomnessSource::Collaborative
+        && clock.unix_timestamp > game.roll_deadline
+        && game.rolls.iter().all(|r| r.is_none())
+        && game.seeds.iter().any(|s| s.is_some())
+    {
+        roll_game_seed(game, &game_key, clock.unix_timestamp)?;
     }
 
-    game.winner = Some(game.players[winner_index]);
-    game.status = GameStatus::Completed;
-    game.completed_at = Some(clock.unix_timestamp);
-
-    // Calculate and transfer prize
-    let fee = game.total_pool * 25 / 1000; // 2.5% fee
-    let prize = game.total_pool - fee;
+    // Check if all players have rolled (or forfeited by missing their reveal)
+    require!(game.all_rolled(clock.unix_timestamp), CustomError::WaitingForRolls);
 
-    emit!(GameCompleted {
-        game_id: game.game_id,
-        winner: game.players[winner_index],
-        prize_amount: prize,
-        winner_roll: highest_roll,
-    });
+    settle_game(
+        game,
+        &game_key,
+        &Escrow {
+            account: &ctx.accounts.escrow,
+            system_program: &ctx.accounts.system_program,
+        },
+        &mut ctx.accounts.treasury,
+        &mut ctx.accounts.jackpot,
+        ctx.remaining_accounts,
+        clock.unix_timestamp,
+    )?;
 
     Ok(())
 }
@@ -61,7 +67,12 @@ pub struct GameCompleted {
     pub game_id: u64,
     pub winner: Pubkey,
     pub prize_amount: u64,
+    pub creator_rake: u64,
+    pub platform_fee: u64,
     pub winner_roll: u8,
+    pub winners: Vec<Pubkey>,
+    pub round: u8,
+    pub payouts: Vec<Payout>,
 }
 
 #[error_code]
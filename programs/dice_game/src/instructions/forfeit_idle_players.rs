@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use crate::escrow::Escrow;
+use crate::settlement::settle_game;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct ForfeitIdlePlayers<'info> {
+    #[account(
+        mut,
+        constraint = game_account.status == GameStatus::Playing @ CustomError::GameNotPlaying,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut, seeds = [b"jackpot"], bump = jackpot.bump)]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Anyone can call this once the round's roll deadline has passed. Players who haven't
+// rolled by then, including oracle requests still unfulfilled, lose their seat; their
+// stakes stay in the pot and the game settles among those who rolled. If every seat in a
+// roll-off goes idle, it settles on the rolls of the earlier rounds.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, ForfeitIdlePlayers<'info>>) -> Result<()> {
+    let game_key = ctx.accounts.game_account.key();
+    let game = &mut ctx.accounts.game_account;
+    let clock = Clock::get()?;
+
+    require!(clock.unix_timestamp > game.roll_deadline, CustomError::RollDeadlineNotReached);
+
+    let idle = game.idle_players(clock.unix_timestamp);
+    require!(!idle.is_empty(), CustomError::NoIdlePlayers);
+
+    // Forfeited seats drop out of the round, so they can no longer roll
+    for &i in &idle {
+        game.forfeited[i] = true;
+        game.in_round[i] = false;
+    }
+
+    emit!(PlayersForfeited {
+        game_id: game.game_id,
+        round: game.round,
+        players: idle.iter().map(|&i| game.players[i]).collect(),
+    });
+
+    // Someone has to have rolled for there to be a result; a game where nobody ever rolled
+    // is left to abort_stale_game
+    require!(game.all_rolled(clock.unix_timestamp), CustomError::NobodyRolled);
+
+    settle_game(
+        game,
+        &game_key,
+        &Escrow {
+            account: &ctx.accounts.escrow,
+            system_program: &ctx.accounts.system_program,
+        },
+        &mut ctx.accounts.treasury,
+        &mut ctx.accounts.jackpot,
+        ctx.remaining_accounts,
+        clock.unix_timestamp,
+    )?;
+
+    Ok(())
+}
+
+#[event]
+pub struct PlayersForfeited {
+    pub game_id: u64,
+    pub round: u8,
+    pub players: Vec<Pubkey>,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Game is not in playing state")]
+    GameNotPlaying,
+    #[msg("Roll deadline has not passed yet")]
+    RollDeadlineNotReached,
+    #[msg("Every player in the round has rolled")]
+    NoIdlePlayers,
+    #[msg("Nobody has rolled in this game")]
+    NobodyRolled,
+}
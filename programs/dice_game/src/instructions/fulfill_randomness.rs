@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use dice_entropy::EntropyVersion;
+use crate::instructions::roll_dice::DiceRolled;
+use crate::escrow::Escrow;
+use crate::settlement::settle_game;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct FulfillRandomness<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = oracle @ CustomError::UnauthorizedOracle,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub oracle: Signer<'info>,
+
+    // Closed back to the player who opened it; the roll keeps the slot and randomness used
+    #[account(
+        mut,
+        close = player,
+        seeds = [
+            b"randomness_request",
+            game_account.key().as_ref(),
+            randomness_request.player.as_ref(),
+            &[randomness_request.round],
+        ],
+        bump = randomness_request.bump,
+        constraint = !randomness_request.fulfilled @ CustomError::AlreadyFulfilled,
+    )]
+    pub randomness_request: Account<'info, RandomnessRequest>,
+
+    #[account(mut, address = randomness_request.player @ CustomError::WrongPlayer)]
+    pub player: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = randomness_request.game,
+        constraint = game_account.status == GameStatus::Playing @ CustomError::GameNotPlaying,
+        constraint = game_account.randomness == RandomnessSource::Oracle @ CustomError::NotOracleGame,
+        constraint = game_account.round == randomness_request.round @ CustomError::StaleRequest,
+        // Requests opened before a closed game_id was reused belong to the old game
+        constraint = randomness_request.game_nonce == game_account.nonce @ CustomError::StaleRequest,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut, seeds = [b"jackpot"], bump = jackpot.bump)]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FulfillRandomness<'info>>, randomness: [u8; 32]) -> Result<()> {
+    let game_key = ctx.accounts.game_account.key();
+    let request = &mut ctx.accounts.randomness_request;
+    let game = &mut ctx.accounts.game_account;
+    let clock = Clock::get()?;
+
+    let player_index = request.player_index as usize;
+    require!(game.rolls[player_index].is_none(), CustomError::AlreadyRolled);
+
+    request.fulfilled = true;
+    request.randomness = randomness;
+
+    // Game, player and seat are hashed in too, so one oracle value can't settle two rolls
+    let entropy = RollEntropy {
+        version: EntropyVersion::Sha256V1 as u8,
+        slot: request.requested_slot,
+        seed: randomness,
+        nonce: game.roll_index(player_index),
+        ..Default::default()
+    };
+    let roll = game.record_roll(&game_key, player_index, entropy, clock.unix_timestamp)?;
+
+    emit!(DiceRolled {
+        game_id: game.game_id,
+        player: request.player,
+        dice1: roll.dice1,
+        dice2: roll.dice2,
+        total: roll.total,
+        round: roll.round,
+    });
+
+    if game.all_rolled(clock.unix_timestamp) {
+        // Prize transfer happens in claim_prize
+        settle_game(
+            game,
+            &game_key,
+            &Escrow {
+                account: &ctx.accounts.escrow,
+                system_program: &ctx.accounts.system_program,
+            },
+            &mut ctx.accounts.treasury,
+            &mut ctx.accounts.jackpot,
+            ctx.remaining_accounts,
+            clock.unix_timestamp,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Signer is not the configured oracle")]
+    UnauthorizedOracle,
+    #[msg("Randomness request already fulfilled")]
+    AlreadyFulfilled,
+    #[msg("Game is not in playing state")]
+    GameNotPlaying,
+    #[msg("Game does not use oracle randomness")]
+    NotOracleGame,
+    #[msg("Randomness request belongs to an earlier round or game")]
+    StaleRequest,
+    #[msg("Player already rolled dice")]
+    AlreadyRolled,
+    #[msg("Player does not match the randomness request")]
+    WrongPlayer,
+}
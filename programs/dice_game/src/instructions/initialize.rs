@@ -1,9 +1,89 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use crate::state::*;
 
 #[derive(Accounts)]
-pub struct Initialize {}
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = ProgramConfig::space(),
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Treasury::space(),
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Jackpot::space(),
+        seeds = [b"jackpot"],
+        bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    // Only the program's upgrade authority may initialize it, so the config can't be
+    // claimed by whoever lands the first transaction after deployment
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ CustomError::NotUpgradeAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<Initialize>, params: ConfigParams) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    config.admin = ctx.accounts.admin.key();
+    config.bump = ctx.bumps.config;
+    config.apply(&params)?;
+
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.total_accrued = 0;
+    treasury.total_withdrawn = 0;
+    treasury.games_settled = 0;
+    treasury.games_created = 0;
+    treasury.bump = ctx.bumps.treasury;
+
+    let jackpot = &mut ctx.accounts.jackpot;
+    jackpot.balance = 0;
+    jackpot.total_contributed = 0;
+    jackpot.total_paid = 0;
+    jackpot.wins = 0;
+    jackpot.bump = ctx.bumps.jackpot;
+
+    emit!(ConfigUpdated {
+        admin: config.admin,
+        params,
+    });
 
-pub fn handler(ctx: Context<Initialize>) -> Result<()> {
-    msg!("Greetings from: {:?}", ctx.program_id);
     Ok(())
 }
+
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub params: ConfigParams,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Only the program's upgrade authority can initialize it")]
+    NotUpgradeAuthority,
+}
@@ -1,5 +1,9 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::escrow::Escrow;
+use crate::referral::link_referrer;
 use crate::state::*;
+use crate::token::TokenAccounts;
 use crate::instructions::start_game::GameStarted;
 
 #[derive(Accounts)]
@@ -15,31 +19,106 @@ pub struct JoinGame<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
 
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    /// CHECK: the player's referral PDA, read if it exists and opened when naming a referrer
+    #[account(
+        mut,
+        seeds = [b"referral", player.key().as_ref()],
+        bump
+    )]
+    pub referral: UncheckedAccount<'info>,
+
+    // Token games only
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", game_account.key().as_ref()],
+        bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<JoinGame>) -> Result<()> {
+pub fn handler(
+    ctx: Context<JoinGame>,
+    seed_commitment: Option<[u8; 32]>,
+    referrer: Option<Pubkey>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    require!(
+        clock.unix_timestamp <= ctx.accounts.game_account.join_deadline,
+        CustomError::JoinClosed
+    );
+
+    // Collaborative games need a seed commitment from every player, other games none
+    let collaborative = ctx.accounts.game_account.randomness == RandomnessSource::Collaborative;
+    require!(seed_commitment.is_some() == collaborative, CustomError::InvalidSeedCommitment);
+
+    let player_referrer = link_referrer(
+        &ctx.accounts.referral,
+        ctx.bumps.referral,
+        &ctx.accounts.player,
+        &ctx.accounts.system_program,
+        referrer,
+    )?;
+
     // Get entry fee before borrowing game mutably
     let entry_fee = ctx.accounts.game_account.entry_fee;
+    let escrow = Escrow {
+        account: &ctx.accounts.escrow,
+        system_program: &ctx.accounts.system_program,
+    };
 
-    // Transfer entry fee from player to game account
-    anchor_lang::system_program::transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            anchor_lang::system_program::Transfer {
-                from: ctx.accounts.player.to_account_info(),
-                to: ctx.accounts.game_account.to_account_info(),
-            }
-        ),
+    let deposited = if ctx.accounts.game_account.mint.is_some() {
+        // Transfer entry fee from player's token account to the vault
+        TokenAccounts::require(
+            &ctx.accounts.mint,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.player_token_account,
+            &ctx.accounts.token_program,
+            &None,
+        )?
+        .deposit(&ctx.accounts.player, entry_fee)?
+    } else {
+        // Transfer entry fee from player to the escrow
+        escrow.deposit(&ctx.accounts.player.to_account_info(), entry_fee)?;
         entry_fee
-    )?;
+    };
 
     let game = &mut ctx.accounts.game_account;
 
     // Add player to the game
     game.players.push(ctx.accounts.player.key());
     game.current_players += 1;
-    game.total_pool += entry_fee;
+    // The pool counts what arrived, which is less than the entry fee under a transfer fee
+    game.total_pool += deposited;
+    let seat = game.players.len() - 1;
+    game.deposits[seat] = deposited;
+    game.referrers[seat] = player_referrer;
+    game.seat_ids[seat] = game.next_seat_id;
+    game.next_seat_id += 1;
+    escrow.check(game)?;
+
+    if let Some(commitment) = seed_commitment {
+        game.commitments[seat] = Some(RollCommitment {
+            commitment,
+            committed_slot: clock.slot,
+            reveal_deadline: 0, // set when the game starts
+        });
+    }
 
     emit!(PlayerJoined {
         game_id: game.game_id,
@@ -49,9 +128,7 @@ pub fn handler(ctx: Context<JoinGame>) -> Result<()> {
 
     // Auto-start if max players reached
     if game.current_players == game.max_players {
-        let clock = Clock::get()?;
-        game.status = GameStatus::Playing;
-        game.started_at = Some(clock.unix_timestamp);
+        game.start(clock.unix_timestamp);
 
         emit!(GameStarted {
             game_id: game.game_id,
@@ -79,4 +156,8 @@ pub enum CustomError {
     GameNotWaiting,
     #[msg("Player already joined this game")]
     AlreadyJoined,
+    #[msg("Seed commitment is required for collaborative games and only allowed there")]
+    InvalidSeedCommitment,
+    #[msg("Join deadline has passed")]
+    JoinClosed,
 }
\ No newline at end of file
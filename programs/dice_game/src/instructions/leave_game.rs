@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::memo::Memo;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::escrow::Escrow;
+use crate::state::*;
+use crate::token::TokenAccounts;
+
+#[derive(Accounts)]
+pub struct LeaveGame<'info> {
+    #[account(
+        mut,
+        constraint = game_account.status == GameStatus::Waiting @ CustomError::GameNotWaiting,
+        constraint = game_account.players.contains(&player.key()) @ CustomError::PlayerNotInGame,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    // Token games only
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", game_account.key().as_ref()],
+        bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // Only needed when the destination token account requires incoming memos
+    pub memo_program: Option<Program<'info, Memo>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Unlike emergency_withdraw this needs no expiry: any player may back out while the
+// game is still waiting, getting back exactly what they paid in.
+pub fn handler(ctx: Context<LeaveGame>) -> Result<()> {
+    let clock = Clock::get()?;
+    let player_key = ctx.accounts.player.key();
+
+    let (player_index, deposit) = {
+        let game = &ctx.accounts.game_account;
+        let player_index = game.players
+            .iter()
+            .position(|p| *p == player_key)
+            .ok_or(CustomError::PlayerNotInGame)?;
+        (player_index, game.deposits[player_index])
+    };
+
+    // Return the player's deposit
+    let escrow = Escrow {
+        account: &ctx.accounts.escrow,
+        system_program: &ctx.accounts.system_program,
+    };
+    if ctx.accounts.game_account.mint.is_some() {
+        TokenAccounts::require(
+            &ctx.accounts.mint,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.player_token_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.memo_program,
+        )?
+        .withdraw(&ctx.accounts.game_account, deposit)?;
+    } else {
+        escrow.withdraw(&ctx.accounts.game_account, &ctx.accounts.player.to_account_info(), deposit)?;
+    }
+
+    let game = &mut ctx.accounts.game_account;
+    game.leave(player_index, clock.unix_timestamp);
+    escrow.check(game)?;
+
+    emit!(PlayerLeft {
+        game_id: game.game_id,
+        player: player_key,
+        refunded: deposit,
+        current_players: game.current_players,
+        creator: game.creator,
+        cancelled: game.status == GameStatus::Cancelled,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct PlayerLeft {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub refunded: u64,
+    pub current_players: u8,
+    // The creator after this player left, which changes when the creator leaves
+    pub creator: Pubkey,
+    pub cancelled: bool,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Game is not waiting for players")]
+    GameNotWaiting,
+    #[msg("Player not in this game")]
+    PlayerNotInGame,
+}
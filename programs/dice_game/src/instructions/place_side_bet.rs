@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use crate::escrow::Escrow;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct PlaceSideBet<'info> {
+    // Betting closes once the game moves to Playing
+    #[account(
+        mut,
+        constraint = game_account.status == GameStatus::Waiting @ CustomError::BettingClosed,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    #[account(
+        init,
+        payer = bettor,
+        space = SideBet::space(),
+        seeds = [b"side_bet", game_account.key().as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub side_bet: Account<'info, SideBet>,
+
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<PlaceSideBet>, pick: Pubkey, amount: u64) -> Result<()> {
+    require!(amount > 0, CustomError::InvalidAmount);
+
+    let seat = ctx.accounts.game_account.players
+        .iter()
+        .position(|p| *p == pick)
+        .ok_or(CustomError::PickNotInGame)?;
+
+    // Transfer the stake from the bettor to the escrow
+    let escrow = Escrow {
+        account: &ctx.accounts.escrow,
+        system_program: &ctx.accounts.system_program,
+    };
+    escrow.deposit(&ctx.accounts.bettor.to_account_info(), amount)?;
+
+    let side_bet = &mut ctx.accounts.side_bet;
+    side_bet.game = ctx.accounts.game_account.key();
+    side_bet.bettor = ctx.accounts.bettor.key();
+    side_bet.pick = pick;
+    side_bet.seat_id = ctx.accounts.game_account.seat_ids[seat];
+    side_bet.amount = amount;
+    side_bet.bump = ctx.bumps.side_bet;
+
+    let game = &mut ctx.accounts.game_account;
+    game.side_pool += amount;
+    game.side_stakes[seat] += amount;
+    game.side_held += amount;
+    escrow.check(game)?;
+
+    emit!(SideBetPlaced {
+        game_id: game.game_id,
+        bettor: side_bet.bettor,
+        pick,
+        amount,
+        side_pool: game.side_pool,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct SideBetPlaced {
+    pub game_id: u64,
+    pub bettor: Pubkey,
+    pub pick: Pubkey,
+    pub amount: u64,
+    pub side_pool: u64,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Side betting is closed once the game starts")]
+    BettingClosed,
+    #[msg("Side bet must be greater than zero")]
+    InvalidAmount,
+    #[msg("Picked player is not in this game")]
+    PickNotInGame,
+}
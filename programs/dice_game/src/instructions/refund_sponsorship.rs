@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use crate::escrow::Escrow;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct RefundSponsorship<'info> {
+    #[account(
+        mut,
+        constraint = matches!(
+            game_account.status,
+            GameStatus::Cancelled | GameStatus::Completed
+        ) @ CustomError::GameNotFinished,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    // Closed back to the sponsor along with the refund. It must be from this game, not an
+    // earlier one under the same game_id whose sponsor_pool is long gone. Anyone may refund
+    // a sponsorship, so an unclaimed one can't keep close_game from running
+    #[account(
+        mut,
+        close = sponsor,
+        seeds = [b"sponsorship", game_account.key().as_ref(), sponsor.key().as_ref()],
+        bump = sponsorship.bump,
+        has_one = sponsor,
+        constraint = sponsorship.game_nonce == game_account.nonce @ CustomError::StaleSponsorship,
+    )]
+    pub sponsorship: Account<'info, Sponsorship>,
+
+    #[account(mut)]
+    pub sponsor: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// A cancelled game refunds the sponsorship; in a completed game it was paid out with the
+// prize pool, so only the account's rent comes back.
+pub fn handler(ctx: Context<RefundSponsorship>) -> Result<()> {
+    let amount = match ctx.accounts.game_account.status {
+        GameStatus::Cancelled => ctx.accounts.sponsorship.amount,
+        _ => 0,
+    };
+
+    // Sponsor money is tracked apart from entry fees, so it goes back to who put it in
+    let escrow = Escrow {
+        account: &ctx.accounts.escrow,
+        system_program: &ctx.accounts.system_program,
+    };
+    escrow.withdraw(&ctx.accounts.game_account, &ctx.accounts.sponsor.to_account_info(), amount)?;
+
+    let game = &mut ctx.accounts.game_account;
+    game.sponsor_pool -= amount;
+    escrow.check(game)?;
+
+    emit!(SponsorshipRefunded {
+        game_id: game.game_id,
+        sponsor: ctx.accounts.sponsor.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct SponsorshipRefunded {
+    pub game_id: u64,
+    pub sponsor: Pubkey,
+    pub amount: u64,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Game is neither cancelled nor completed")]
+    GameNotFinished,
+    #[msg("Sponsorship belongs to an earlier game with this game_id")]
+    StaleSponsorship,
+}
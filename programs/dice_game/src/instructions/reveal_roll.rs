@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use dice_entropy::EntropyVersion;
+use crate::instructions::roll_dice::DiceRolled;
+use crate::randomness::{roll_commitment, slot_hash_at};
+use crate::escrow::Escrow;
+use crate::settlement::settle_game;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct RevealRoll<'info> {
+    #[account(
+        mut,
+        constraint = game_account.status == GameStatus::Playing @ CustomError::GameNotPlaying,
+        constraint = game_account.randomness == RandomnessSource::CommitReveal @ CustomError::NotCommitReveal,
+        constraint = game_account.players.contains(&player.key()) @ CustomError::PlayerNotInGame,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    pub player: Signer<'info>,
+
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut, seeds = [b"jackpot"], bump = jackpot.bump)]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    /// CHECK: address is verified when the slot hash is read
+    pub slot_hashes: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, RevealRoll<'info>>, secret: [u8; 32]) -> Result<()> {
+    let game_key = ctx.accounts.game_account.key();
+    let player_key = ctx.accounts.player.key();
+    let game = &mut ctx.accounts.game_account;
+    let clock = Clock::get()?;
+
+    let player_index = game.players
+        .iter()
+        .position(|p| p == &player_key)
+        .ok_or(CustomError::PlayerNotInGame)?;
+
+    require!(game.in_round[player_index], CustomError::NotInRound);
+    require!(game.rolls[player_index].is_none(), CustomError::AlreadyRolled);
+    let commitment = game.commitments[player_index].ok_or(CustomError::NotCommitted)?;
+
+    require!(clock.unix_timestamp <= commitment.reveal_deadline, CustomError::RevealDeadlinePassed);
+    require!(clock.slot > commitment.committed_slot, CustomError::RevealTooEarly);
+    require!(
+        roll_commitment(&secret, &player_key) == commitment.commitment,
+        CustomError::InvalidReveal
+    );
+
+    // Mix the secret with the hash of the commit slot, unknown when the commitment was made
+    let slot_hash = slot_hash_at(&ctx.accounts.slot_hashes, commitment.committed_slot)?;
+    let entropy = RollEntropy {
+        version: EntropyVersion::Sha256V1 as u8,
+        slot: commitment.committed_slot,
+        slot_hash,
+        seed: secret,
+        nonce: game.roll_index(player_index),
+    };
+    let roll = game.record_roll(&game_key, player_index, entropy, clock.unix_timestamp)?;
+
+    emit!(DiceRolled {
+        game_id: game.game_id,
+        player: player_key,
+        dice1: roll.dice1,
+        dice2: roll.dice2,
+        total: roll.total,
+        round: roll.round,
+    });
+
+    if game.all_rolled(clock.unix_timestamp) {
+        // Prize transfer happens in claim_prize
+        settle_game(
+            game,
+            &game_key,
+            &Escrow {
+                account: &ctx.accounts.escrow,
+                system_program: &ctx.accounts.system_program,
+            },
+            &mut ctx.accounts.treasury,
+            &mut ctx.accounts.jackpot,
+            ctx.remaining_accounts,
+            clock.unix_timestamp,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Game is not in playing state")]
+    GameNotPlaying,
+    #[msg("Game does not use commit-reveal rolls")]
+    NotCommitReveal,
+    #[msg("Player not in this game")]
+    PlayerNotInGame,
+    #[msg("Player is not in the current roll-off round")]
+    NotInRound,
+    #[msg("Player already rolled dice")]
+    AlreadyRolled,
+    #[msg("Player has not committed a roll")]
+    NotCommitted,
+    #[msg("Reveal deadline has passed")]
+    RevealDeadlinePassed,
+    #[msg("Reveal must land after the commit slot")]
+    RevealTooEarly,
+    #[msg("Secret does not match the commitment")]
+    InvalidReveal,
+}
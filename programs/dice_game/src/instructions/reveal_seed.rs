@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use crate::instructions::roll_dice::DiceRolled;
+use crate::randomness::roll_commitment;
+use crate::escrow::Escrow;
+use crate::settlement::settle_game;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct RevealSeed<'info> {
+    #[account(
+        mut,
+        constraint = game_account.status == GameStatus::Playing @ CustomError::GameNotPlaying,
+        constraint = game_account.randomness == RandomnessSource::Collaborative @ CustomError::NotCollaborative,
+        constraint = game_account.players.contains(&player.key()) @ CustomError::PlayerNotInGame,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    pub player: Signer<'info>,
+
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut, seeds = [b"jackpot"], bump = jackpot.bump)]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, RevealSeed<'info>>, seed: [u8; 32]) -> Result<()> {
+    let game_key = ctx.accounts.game_account.key();
+    let player_key = ctx.accounts.player.key();
+    let game = &mut ctx.accounts.game_account;
+    let clock = Clock::get()?;
+
+    let player_index = game.players
+        .iter()
+        .position(|p| p == &player_key)
+        .ok_or(CustomError::PlayerNotInGame)?;
+
+    require!(game.seeds[player_index].is_none(), CustomError::AlreadyRevealed);
+    let commitment = game.commitments[player_index].ok_or(CustomError::NotCommitted)?;
+
+    require!(clock.unix_timestamp <= commitment.reveal_deadline, CustomError::RevealDeadlinePassed);
+    require!(
+        roll_commitment(&seed, &player_key) == commitment.commitment,
+        CustomError::InvalidReveal
+    );
+
+    game.seeds[player_index] = Some(seed);
+
+    emit!(SeedRevealed {
+        game_id: game.game_id,
+        player: player_key,
+    });
+
+    if game.all_seeds_revealed() {
+        roll_game_seed(game, &game_key, clock.unix_timestamp)?;
+        // Prize transfer happens in claim_prize
+        settle_game(
+            game,
+            &game_key,
+            &Escrow {
+                account: &ctx.accounts.escrow,
+                system_program: &ctx.accounts.system_program,
+            },
+            &mut ctx.accounts.treasury,
+            &mut ctx.accounts.jackpot,
+            ctx.remaining_accounts,
+            clock.unix_timestamp,
+        )?;
+    }
+
+    Ok(())
+}
+
+// Rolls every revealing player in the round from the combined seed. Also called by
+// finalize_game once the roll deadline passes with seeds still missing, and by
+// settle_game for each roll-off round.
+pub fn roll_game_seed(game: &mut GameAccount, game_key: &Pubkey, now: i64) -> Result<()> {
+    let game_seed = game.roll_from_seeds(game_key, now)?;
+
+    emit!(GameSeedDerived {
+        game_id: game.game_id,
+        game_seed,
+        revealed: game.seeds.iter().flatten().count() as u8,
+    });
+
+    for (i, (player, roll)) in game.players.iter().zip(game.rolls.iter()).enumerate() {
+        if let (Some(roll), true) = (roll, game.in_round[i]) {
+            emit!(DiceRolled {
+                game_id: game.game_id,
+                player: *player,
+                dice1: roll.dice1,
+                dice2: roll.dice2,
+                total: roll.total,
+                round: roll.round,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[event]
+pub struct SeedRevealed {
+    pub game_id: u64,
+    pub player: Pubkey,
+}
+
+#[event]
+pub struct GameSeedDerived {
+    pub game_id: u64,
+    pub game_seed: [u8; 32],
+    pub revealed: u8,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Game is not in playing state")]
+    GameNotPlaying,
+    #[msg("Game does not use collaborative randomness")]
+    NotCollaborative,
+    #[msg("Player not in this game")]
+    PlayerNotInGame,
+    #[msg("Player already revealed their seed")]
+    AlreadyRevealed,
+    #[msg("Player has no seed commitment")]
+    NotCommitted,
+    #[msg("Reveal deadline has passed")]
+    RevealDeadlinePassed,
+    #[msg("Seed does not match the commitment")]
+    InvalidReveal,
+}
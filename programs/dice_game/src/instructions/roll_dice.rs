@@ -1,4 +1,9 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use dice_entropy::EntropyVersion;
+use crate::randomness::recent_slot_hash;
+use crate::escrow::Escrow;
+use crate::settlement::settle_game;
 use crate::state::*;
 
 #[derive(Accounts)]
@@ -6,6 +11,10 @@ pub struct RollDice<'info> {
     #[account(
         mut,
         constraint = game_account.status == GameStatus::Playing @ CustomError::GameNotPlaying,
+        constraint = matches!(
+            game_account.randomness,
+            RandomnessSource::SlotHashes | RandomnessSource::Oracle
+        ) @ CustomError::WrongRandomnessSource,
         constraint = game_account.players.contains(&player.key()) @ CustomError::PlayerNotInGame,
     )]
     pub game_account: Account<'info, GameAccount>,
@@ -13,10 +22,43 @@ pub struct RollDice<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
 
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut, seeds = [b"jackpot"], bump = jackpot.bump)]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    // Only needed for oracle games; opened here and settled by fulfill_randomness
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = RandomnessRequest::space(),
+        seeds = [
+            b"randomness_request",
+            game_account.key().as_ref(),
+            player.key().as_ref(),
+            &[game_account.round],
+        ],
+        bump
+    )]
+    pub randomness_request: Option<Account<'info, RandomnessRequest>>,
+
+    /// CHECK: the SlotHashes sysvar, read in place because it is too large to deserialize
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<RollDice>) -> Result<()> {
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, RollDice<'info>>) -> Result<()> {
+    let game_key = ctx.accounts.game_account.key();
     let game = &mut ctx.accounts.game_account;
     let clock = Clock::get()?;
 
@@ -26,64 +68,83 @@ pub fn handler(ctx: Context<RollDice>) -> Result<()> {
         .position(|p| p == &ctx.accounts.player.key())
         .ok_or(CustomError::PlayerNotInGame)?;
 
-    // Check if player already rolled
+    // Check if player is still in the running and hasn't rolled this round
+    require!(game.in_round[player_index], CustomError::NotInRound);
     require!(game.rolls[player_index].is_none(), CustomError::AlreadyRolled);
+    // Past the deadline an idle player can only be forfeited
+    require!(clock.unix_timestamp <= game.roll_deadline, CustomError::RollDeadlinePassed);
+
+    if game.randomness == RandomnessSource::Oracle {
+        let request = ctx.accounts.randomness_request
+            .as_mut()
+            .ok_or(CustomError::MissingRandomnessRequest)?;
+
+        // An existing request can only be left over from a closed game with the same game_id
+        require!(
+            request.game == Pubkey::default() || request.game_nonce != game.nonce,
+            CustomError::AlreadyRequested
+        );
+
+        request.game = game_key;
+        request.game_nonce = game.nonce;
+        request.player = ctx.accounts.player.key();
+        request.player_index = player_index as u8;
+        request.round = game.round;
+        request.requested_slot = clock.slot;
+        request.requested_at = clock.unix_timestamp;
+        request.fulfilled = false;
+        request.randomness = [0; 32];
+        request.bump = ctx.bumps.randomness_request.unwrap();
+
+        emit!(RandomnessRequested {
+            game_id: game.game_id,
+            player: request.player,
+            request: request.key(),
+            requested_slot: clock.slot,
+        });
+
+        return Ok(());
+    }
 
-    // Generate pseudo-random dice roll
-    // In production, you'd want to use a VRF for true randomness
-    let slot = clock.slot;
-    let dice1 = ((slot % 6) + 1) as u8;
-    let dice2 = (((slot >> 8) % 6) + 1) as u8;
-    let total = dice1 + dice2;
+    // Default source: mix the latest slot hash with the game, player and roll index.
+    // Oracle and commit-reveal games exist for rolls the caller can't predict at all
+    let (slot, slot_hash) = recent_slot_hash(&ctx.accounts.slot_hashes)?;
+    let entropy = RollEntropy {
+        version: EntropyVersion::Sha256V1 as u8,
+        slot,
+        slot_hash,
+        nonce: game.roll_index(player_index),
+        ..Default::default()
+    };
 
     // Record the roll
-    game.rolls[player_index] = Some(DiceRoll {
-        dice1,
-        dice2,
-        total,
-        rolled_at: clock.unix_timestamp,
-    });
+    let roll = game.record_roll(&game_key, player_index, entropy, clock.unix_timestamp)?;
 
     emit!(DiceRolled {
         game_id: game.game_id,
         player: ctx.accounts.player.key(),
-        dice1,
-        dice2,
-        total,
+        dice1: roll.dice1,
+        dice2: roll.dice2,
+        total: roll.total,
+        round: roll.round,
     });
 
     // Check if all players have rolled
-    let all_rolled = game.players
-        .iter()
-        .enumerate()
-        .take(game.current_players as usize)
-        .all(|(i, _)| game.rolls[i].is_some());
-
-    if all_rolled {
-        // Find winner (highest roll)
-        let mut winner_index = 0;
-        let mut highest_roll = 0u8;
-
-        for (i, roll_opt) in game.rolls.iter().enumerate().take(game.current_players as usize) {
-            if let Some(roll) = roll_opt {
-                if roll.total > highest_roll {
-                    highest_roll = roll.total;
-                    winner_index = i;
-                }
-            }
-        }
-
-        game.winner = Some(game.players[winner_index]);
-        game.status = GameStatus::Completed;
-        game.completed_at = Some(clock.unix_timestamp);
-
-        // Calculate prize (minus 2.5% fee)
-        let fee = game.total_pool * 25 / 1000; // 2.5%
-        let prize = game.total_pool - fee;
-
+    if game.all_rolled(clock.unix_timestamp) {
         // Note: Prize transfer happens in a separate claim_prize instruction
         // where the winner provides their account as a signer
-        // GameCompleted event is emitted in finalize_game or claim_prize
+        settle_game(
+            game,
+            &game_key,
+            &Escrow {
+                account: &ctx.accounts.escrow,
+                system_program: &ctx.accounts.system_program,
+            },
+            &mut ctx.accounts.treasury,
+            &mut ctx.accounts.jackpot,
+            ctx.remaining_accounts,
+            clock.unix_timestamp,
+        )?;
     }
 
     Ok(())
@@ -96,16 +157,33 @@ pub struct DiceRolled {
     pub dice1: u8,
     pub dice2: u8,
     pub total: u8,
+    pub round: u8,
 }
 
-// GameCompleted event moved to finalize_game.rs to avoid duplication
+#[event]
+pub struct RandomnessRequested {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub request: Pubkey,
+    pub requested_slot: u64,
+}
 
 #[error_code]
 pub enum CustomError {
     #[msg("Game is not in playing state")]
     GameNotPlaying,
+    #[msg("Commit-reveal and collaborative games roll through their reveal instructions")]
+    WrongRandomnessSource,
     #[msg("Player not in this game")]
     PlayerNotInGame,
+    #[msg("Player is not in the current roll-off round")]
+    NotInRound,
     #[msg("Player already rolled dice")]
     AlreadyRolled,
-}
\ No newline at end of file
+    #[msg("Oracle games need a randomness request account")]
+    MissingRandomnessRequest,
+    #[msg("Randomness already requested for this round")]
+    AlreadyRequested,
+    #[msg("Roll deadline for this round has passed")]
+    RollDeadlinePassed,
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use crate::escrow::Escrow;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct SettleSideBet<'info> {
+    #[account(
+        mut,
+        constraint = matches!(
+            game_account.status,
+            GameStatus::Completed | GameStatus::Cancelled
+        ) @ CustomError::GameNotFinished,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    // Closed back to the bettor once paid. Anyone may settle a bet, so finished games can
+    // be cleared for close_game
+    #[account(
+        mut,
+        close = bettor,
+        seeds = [b"side_bet", game_account.key().as_ref(), bettor.key().as_ref()],
+        bump = side_bet.bump,
+        has_one = bettor,
+    )]
+    pub side_bet: Account<'info, SideBet>,
+
+    #[account(mut)]
+    pub bettor: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<SettleSideBet>) -> Result<()> {
+    let game = &mut ctx.accounts.game_account;
+    let side_bet = &ctx.accounts.side_bet;
+
+    // Winning bets settle from the final ranking; cancelled games refund every bet
+    let payout = game.side_bet_payout(side_bet);
+
+    let escrow = Escrow {
+        account: &ctx.accounts.escrow,
+        system_program: &ctx.accounts.system_program,
+    };
+    escrow.withdraw(game, &ctx.accounts.bettor.to_account_info(), payout)?;
+
+    game.side_pool -= side_bet.amount;
+    game.side_held -= payout;
+    escrow.check(game)?;
+
+    emit!(SideBetSettled {
+        game_id: game.game_id,
+        bettor: side_bet.bettor,
+        pick: side_bet.pick,
+        amount: side_bet.amount,
+        payout,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct SideBetSettled {
+    pub game_id: u64,
+    pub bettor: Pubkey,
+    pub pick: Pubkey,
+    pub amount: u64,
+    pub payout: u64,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Game is neither completed nor cancelled")]
+    GameNotFinished,
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use crate::escrow::Escrow;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct SponsorGame<'info> {
+    #[account(
+        mut,
+        constraint = matches!(
+            game_account.status,
+            GameStatus::Waiting | GameStatus::Playing
+        ) @ CustomError::GameFinished,
+        constraint = game_account.mint.is_none() @ CustomError::TokenGame,
+    )]
+    pub game_account: Account<'info, GameAccount>,
+
+    // One per sponsor and game; repeat sponsoring adds to it
+    #[account(
+        init_if_needed,
+        payer = sponsor,
+        space = Sponsorship::space(),
+        seeds = [b"sponsorship", game_account.key().as_ref(), sponsor.key().as_ref()],
+        bump
+    )]
+    pub sponsorship: Account<'info, Sponsorship>,
+
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", game_account.key().as_ref()],
+        bump = game_account.escrow_bump,
+    )]
+    pub escrow: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<SponsorGame>, amount: u64) -> Result<()> {
+    require!(amount > 0, CustomError::InvalidAmount);
+
+    // Transfer the contribution from the sponsor to the escrow
+    let escrow = Escrow {
+        account: &ctx.accounts.escrow,
+        system_program: &ctx.accounts.system_program,
+    };
+    escrow.deposit(&ctx.accounts.sponsor.to_account_info(), amount)?;
+
+    // A sponsorship left over from a closed game with the same game_id starts from zero
+    let nonce = ctx.accounts.game_account.nonce;
+    let sponsorship = &mut ctx.accounts.sponsorship;
+    if sponsorship.game_nonce != nonce {
+        sponsorship.amount = 0;
+    }
+    sponsorship.game = ctx.accounts.game_account.key();
+    sponsorship.game_nonce = nonce;
+    sponsorship.sponsor = ctx.accounts.sponsor.key();
+    sponsorship.amount += amount;
+    sponsorship.bump = ctx.bumps.sponsorship;
+
+    let game = &mut ctx.accounts.game_account;
+    game.sponsor_pool += amount;
+    escrow.check(game)?;
+
+    emit!(GameSponsored {
+        game_id: game.game_id,
+        sponsor: sponsorship.sponsor,
+        amount,
+        sponsor_pool: game.sponsor_pool,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct GameSponsored {
+    pub game_id: u64,
+    pub sponsor: Pubkey,
+    pub amount: u64,
+    pub sponsor_pool: u64,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Game already completed or cancelled")]
+    GameFinished,
+    #[msg("Token games can't be sponsored in lamports")]
+    TokenGame,
+    #[msg("Sponsorship must be greater than zero")]
+    InvalidAmount,
+}
@@ -6,8 +6,7 @@ pub struct StartGame<'info> {
     #[account(
         mut,
         constraint = game_account.status == GameStatus::Waiting @ CustomError::GameNotWaiting,
-        constraint = game_account.creator == starter.key() @ CustomError::NotCreator,
-        constraint = game_account.current_players >= 2 @ CustomError::NotEnoughPlayers,
+        constraint = game_account.current_players >= game_account.min_players @ CustomError::NotEnoughPlayers,
     )]
     pub game_account: Account<'info, GameAccount>,
 
@@ -18,8 +17,14 @@ pub fn handler(ctx: Context<StartGame>) -> Result<()> {
     let game = &mut ctx.accounts.game_account;
     let clock = Clock::get()?;
 
-    game.status = GameStatus::Playing;
-    game.started_at = Some(clock.unix_timestamp);
+    // The creator can start early; once joining closes anyone can, so players aren't
+    // stuck waiting on a creator who left
+    require!(
+        ctx.accounts.starter.key() == game.creator || clock.unix_timestamp > game.join_deadline,
+        CustomError::NotCreator
+    );
+
+    game.start(clock.unix_timestamp);
 
     emit!(GameStarted {
         game_id: game.game_id,
@@ -39,7 +44,7 @@ pub struct GameStarted {
 pub enum CustomError {
     #[msg("Game is not waiting for players")]
     GameNotWaiting,
-    #[msg("Only creator can start the game")]
+    #[msg("Only creator can start the game before the join deadline")]
     NotCreator,
     #[msg("Not enough players to start")]
     NotEnoughPlayers,
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use crate::instructions::initialize::ConfigUpdated;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::NotAdmin,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+// Running games keep the fee they were created with; new limits apply to new games
+pub fn handler(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.apply(&params)?;
+
+    emit!(ConfigUpdated {
+        admin: config.admin,
+        params,
+    });
+
+    Ok(())
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Only the config admin can do this")]
+    NotAdmin,
+}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CustomError::NotAdmin,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    pub admin: Signer<'info>,
+
+    /// CHECK: only receives lamports; must be the wallet set in the config
+    #[account(mut, address = config.treasury @ CustomError::WrongDestination)]
+    pub treasury_wallet: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+    require!(amount > 0, CustomError::InvalidAmount);
+    require!(amount <= ctx.accounts.treasury.available(), CustomError::InsufficientFees);
+
+    // Only accrued fees leave the treasury, so it always keeps its rent
+    **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **ctx.accounts.treasury_wallet.to_account_info().try_borrow_mut_lamports()? += amount;
+
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.total_withdrawn += amount;
+
+    emit!(TreasuryWithdrawn {
+        destination: ctx.accounts.treasury_wallet.key(),
+        amount,
+        total_accrued: treasury.total_accrued,
+        total_withdrawn: treasury.total_withdrawn,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub total_accrued: u64,
+    pub total_withdrawn: u64,
+}
+
+#[error_code]
+pub enum CustomError {
+    #[msg("Only the config admin can do this")]
+    NotAdmin,
+    #[msg("Destination is not the configured treasury wallet")]
+    WrongDestination,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Amount exceeds the fees available in the treasury")]
+    InsufficientFees,
+}
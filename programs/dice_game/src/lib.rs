@@ -1,7 +1,16 @@
+// The #[program] macro in anchor 0.31 still emits the deprecated AccountInfo::realloc
+#![allow(deprecated)]
+
 pub mod constants;
 pub mod error;
+pub mod escrow;
 pub mod instructions;
+pub mod randomness;
+pub mod referral;
+pub mod refund;
+pub mod settlement;
 pub mod state;
+pub mod token;
 
 use anchor_lang::prelude::*;
 
@@ -15,35 +24,91 @@ declare_id!("42kX7N73TVX16fufFaEaN2nfev4zDTa5TbvdAqXYKPd3");
 pub mod dice_game {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        initialize::handler(ctx)
+    pub fn initialize(ctx: Context<Initialize>, params: ConfigParams) -> Result<()> {
+        initialize::handler(ctx, params)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        update_config::handler(ctx, params)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_game(
         ctx: Context<CreateGame>,
         game_id: u64,
         entry_fee: u64,
         max_players: u8,
+        min_players: u8,
+        randomness: RandomnessSource,
+        seed_commitment: Option<[u8; 32]>,
+        tie_break: TieBreak,
+        payout_tiers: Vec<u16>,
+        creator_rake_bps: u16,
+        referrer: Option<Pubkey>,
+        deadlines: Deadlines,
     ) -> Result<()> {
-        create_game::handler(ctx, game_id, entry_fee, max_players)
+        create_game::handler(
+            ctx,
+            game_id,
+            entry_fee,
+            max_players,
+            min_players,
+            randomness,
+            seed_commitment,
+            tie_break,
+            payout_tiers,
+            creator_rake_bps,
+            referrer,
+            deadlines,
+        )
     }
 
-    pub fn join_game(ctx: Context<JoinGame>) -> Result<()> {
-        join_game::handler(ctx)
+    pub fn join_game(
+        ctx: Context<JoinGame>,
+        seed_commitment: Option<[u8; 32]>,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
+        join_game::handler(ctx, seed_commitment, referrer)
     }
 
     pub fn start_game(ctx: Context<StartGame>) -> Result<()> {
         start_game::handler(ctx)
     }
 
-    pub fn roll_dice(ctx: Context<RollDice>) -> Result<()> {
+    pub fn roll_dice<'info>(ctx: Context<'_, '_, '_, 'info, RollDice<'info>>) -> Result<()> {
         roll_dice::handler(ctx)
     }
 
-    pub fn finalize_game(ctx: Context<FinalizeGame>) -> Result<()> {
+    pub fn commit_roll(ctx: Context<CommitRoll>, commitment: [u8; 32]) -> Result<()> {
+        commit_roll::handler(ctx, commitment)
+    }
+
+    pub fn reveal_roll<'info>(ctx: Context<'_, '_, '_, 'info, RevealRoll<'info>>, secret: [u8; 32]) -> Result<()> {
+        reveal_roll::handler(ctx, secret)
+    }
+
+    pub fn reveal_seed<'info>(ctx: Context<'_, '_, '_, 'info, RevealSeed<'info>>, seed: [u8; 32]) -> Result<()> {
+        reveal_seed::handler(ctx, seed)
+    }
+
+    pub fn fulfill_randomness<'info>(ctx: Context<'_, '_, '_, 'info, FulfillRandomness<'info>>, randomness: [u8; 32]) -> Result<()> {
+        fulfill_randomness::handler(ctx, randomness)
+    }
+
+    pub fn close_randomness_request(ctx: Context<CloseRandomnessRequest>) -> Result<()> {
+        close_randomness_request::handler(ctx)
+    }
+
+    pub fn finalize_game<'info>(ctx: Context<'_, '_, '_, 'info, FinalizeGame<'info>>) -> Result<()> {
         finalize_game::handler(ctx)
     }
 
+    pub fn forfeit_idle_players<'info>(
+        ctx: Context<'_, '_, '_, 'info, ForfeitIdlePlayers<'info>>,
+    ) -> Result<()> {
+        forfeit_idle_players::handler(ctx)
+    }
+
     pub fn emergency_withdraw(ctx: Context<EmergencyWithdraw>) -> Result<()> {
         emergency_withdraw::handler(ctx)
     }
@@ -51,4 +116,50 @@ pub mod dice_game {
     pub fn claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
         claim_prize::handler(ctx)
     }
+
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        withdraw_treasury::handler(ctx, amount)
+    }
+
+    pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+        claim_referral_rewards::handler(ctx)
+    }
+
+    pub fn sponsor_game(ctx: Context<SponsorGame>, amount: u64) -> Result<()> {
+        sponsor_game::handler(ctx, amount)
+    }
+
+    pub fn refund_sponsorship(ctx: Context<RefundSponsorship>) -> Result<()> {
+        refund_sponsorship::handler(ctx)
+    }
+
+    pub fn place_side_bet(ctx: Context<PlaceSideBet>, pick: Pubkey, amount: u64) -> Result<()> {
+        place_side_bet::handler(ctx, pick, amount)
+    }
+
+    pub fn settle_side_bet(ctx: Context<SettleSideBet>) -> Result<()> {
+        settle_side_bet::handler(ctx)
+    }
+
+    pub fn collect_token_fees(ctx: Context<CollectTokenFees>) -> Result<()> {
+        collect_token_fees::handler(ctx)
+    }
+
+    pub fn close_game(ctx: Context<CloseGame>) -> Result<()> {
+        close_game::handler(ctx)
+    }
+
+    pub fn abort_stale_game<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AbortStaleGame<'info>>,
+    ) -> Result<()> {
+        abort_stale_game::handler(ctx)
+    }
+
+    pub fn cancel_game<'info>(ctx: Context<'_, '_, 'info, 'info, CancelGame<'info>>) -> Result<()> {
+        cancel_game::handler(ctx)
+    }
+
+    pub fn leave_game(ctx: Context<LeaveGame>) -> Result<()> {
+        leave_game::handler(ctx)
+    }
 }
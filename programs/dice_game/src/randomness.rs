@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use crate::error::ErrorCode;
+
+// Each SlotHashes entry is a little-endian u64 slot followed by a 32 byte hash
+const SLOT_HASH_ENTRY_LEN: usize = 8 + 32;
+
+// The SlotHashes sysvar is too large to deserialize on-chain, so entries are
+// read straight from the account data. Entries are sorted newest slot first.
+fn slot_hash_entries<'a>(data: &'a [u8]) -> Result<impl Iterator<Item = (u64, [u8; 32])> + 'a> {
+    let len_bytes: [u8; 8] = data
+        .get(..8)
+        .and_then(|b| b.try_into().ok())
+        .ok_or(ErrorCode::InvalidSlotHashes)?;
+    let len = u64::from_le_bytes(len_bytes) as usize;
+
+    Ok(data[8..]
+        .chunks_exact(SLOT_HASH_ENTRY_LEN)
+        .take(len)
+        .map(|entry| {
+            let slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+            (slot, entry[8..].try_into().unwrap())
+        }))
+}
+
+pub fn slot_hash_at(slot_hashes_info: &AccountInfo, slot: u64) -> Result<[u8; 32]> {
+    require_keys_eq!(slot_hashes_info.key(), slot_hashes::ID, ErrorCode::InvalidSlotHashes);
+
+    let data = slot_hashes_info.try_borrow_data()?;
+    let hash = slot_hash_entries(&data)?
+        .take_while(|(entry_slot, _)| *entry_slot >= slot)
+        .find(|(entry_slot, _)| *entry_slot == slot)
+        .map(|(_, hash)| hash);
+    hash.ok_or_else(|| error!(ErrorCode::SlotHashUnavailable))
+}
+
+// Newest entry in SlotHashes: the parent slot of the one the transaction lands in
+pub fn recent_slot_hash(slot_hashes_info: &AccountInfo) -> Result<(u64, [u8; 32])> {
+    require_keys_eq!(slot_hashes_info.key(), slot_hashes::ID, ErrorCode::InvalidSlotHashes);
+
+    let data = slot_hashes_info.try_borrow_data()?;
+    let entry = slot_hash_entries(&data)?.next();
+    entry.ok_or_else(|| error!(ErrorCode::SlotHashUnavailable))
+}
+
+// Commitment a player submits in commit_roll: hash(secret || player)
+pub fn roll_commitment(secret: &[u8; 32], player: &Pubkey) -> [u8; 32] {
+    hashv(&[secret, player.as_ref()]).to_bytes()
+}
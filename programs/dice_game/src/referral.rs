@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{allocate, assign, transfer, Allocate, Assign, Transfer};
+use crate::error::ErrorCode;
+use crate::escrow::Escrow;
+use crate::state::*;
+
+// Called by create_game and join_game with the player's referral PDA, which is always
+// passed so a referred player can't leave their referrer out of a game. The first referrer
+// a player names is stored for good; later names are ignored. The PDA is only opened once
+// a referrer is named, so unreferred players pay no rent for it. Returns the referrer on
+// record so the game can snapshot it for the seat.
+pub fn link_referrer<'info>(
+    referral: &AccountInfo<'info>,
+    bump: u8,
+    player: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    referrer: Option<Pubkey>,
+) -> Result<Option<Pubkey>> {
+    if referral.owner == &crate::ID {
+        let record = Referral::try_deserialize(&mut &referral.try_borrow_data()?[..])?;
+        return Ok(Some(record.referrer));
+    }
+    let Some(referrer) = referrer else {
+        return Ok(None);
+    };
+    require_keys_neq!(referrer, player.key(), ErrorCode::SelfReferral);
+
+    // Open the PDA by hand, topping up whatever lamports it may already hold
+    let space = Referral::space();
+    let rent = Rent::get()?.minimum_balance(space).saturating_sub(referral.lamports());
+    let signer: &[&[u8]] = &[b"referral", player.key.as_ref(), &[bump]];
+    let program = system_program.to_account_info();
+    if rent > 0 {
+        transfer(
+            CpiContext::new(
+                program.clone(),
+                Transfer { from: player.clone(), to: referral.clone() },
+            ),
+            rent,
+        )?;
+    }
+    allocate(
+        CpiContext::new_with_signer(
+            program.clone(),
+            Allocate { account_to_allocate: referral.clone() },
+            &[signer],
+        ),
+        space as u64,
+    )?;
+    assign(
+        CpiContext::new_with_signer(
+            program,
+            Assign { account_to_assign: referral.clone() },
+            &[signer],
+        ),
+        &crate::ID,
+    )?;
+
+    let record = Referral {
+        player: player.key(),
+        referrer,
+        accrued: 0,
+        claimed: 0,
+        bump,
+    };
+    record.try_serialize(&mut &mut referral.try_borrow_mut_data()?[..])?;
+
+    emit!(ReferralLinked { player: player.key(), referrer });
+
+    Ok(Some(referrer))
+}
+
+// Credits each referred seat's referrer with `referral_bps` of that seat's part of the
+// fee. The referral PDAs of referred players must be passed as remaining accounts.
+// Returns the total moved out of the game's escrow into referral accounts.
+pub fn credit_referrals<'info>(
+    game: &Account<'info, GameAccount>,
+    escrow: &Escrow<'_, 'info>,
+    fee: u64,
+    accounts: &[AccountInfo<'info>],
+) -> Result<u64> {
+    let seat_fee = fee / game.current_players as u64;
+    let reward = (seat_fee as u128 * game.referral_bps as u128 / 10_000) as u64;
+    if reward == 0 {
+        return Ok(0);
+    }
+
+    let mut total = 0;
+    for (player, referrer) in game.players.iter().zip(game.referrers.iter()) {
+        let Some(referrer) = referrer else { continue };
+
+        let (address, _) = Pubkey::find_program_address(&[b"referral", player.as_ref()], &crate::ID);
+        let info = accounts
+            .iter()
+            .find(|a| a.key() == address)
+            .ok_or(ErrorCode::MissingReferralAccount)?;
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::MissingReferralAccount);
+
+        let mut referral = Referral::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        referral.accrued += reward;
+        referral.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        escrow.withdraw(game, info, reward)?;
+        total += reward;
+
+        emit!(ReferralAccrued {
+            game_id: game.game_id,
+            player: *player,
+            referrer: *referrer,
+            amount: reward,
+        });
+    }
+
+    Ok(total)
+}
+
+#[event]
+pub struct ReferralLinked {
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+}
+
+#[event]
+pub struct ReferralAccrued {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+    pub amount: u64,
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use anchor_spl::memo::Memo;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::error::ErrorCode;
+use crate::escrow::Escrow;
+use crate::state::*;
+use crate::token::TokenAccounts;
+
+// Returns every seat's deposit in one go, for instructions that cancel a whole game.
+// `accounts` are the players' wallets in seat order, or in token games their token
+// accounts for the game's mint. Clears the pool and returns the total refunded.
+pub fn refund_players<'info>(
+    game: &mut Account<'info, GameAccount>,
+    escrow: &Escrow<'_, 'info>,
+    mint: &Option<InterfaceAccount<'info, Mint>>,
+    vault: &mut Option<InterfaceAccount<'info, TokenAccount>>,
+    token_program: &Option<Interface<'info, TokenInterface>>,
+    memo_program: &Option<Program<'info, Memo>>,
+    accounts: &'info [AccountInfo<'info>],
+) -> Result<u64> {
+    let players = game.current_players as usize;
+    require!(accounts.len() == players, ErrorCode::RefundAccountMismatch);
+
+    let mut refunded = 0;
+    for (seat, info) in accounts.iter().enumerate() {
+        let player = game.players[seat];
+        let deposit = game.deposits[seat];
+
+        if game.mint.is_some() {
+            let user = InterfaceAccount::<TokenAccount>::try_from(info)?;
+            require_keys_eq!(user.owner, player, ErrorCode::RefundAccountMismatch);
+            let user = Some(user);
+            TokenAccounts::require(mint, vault, &user, token_program, memo_program)?
+                .withdraw(game, deposit)?;
+        } else {
+            require_keys_eq!(info.key(), player, ErrorCode::RefundAccountMismatch);
+            escrow.withdraw(game, info, deposit)?;
+        }
+
+        game.deposits[seat] = 0;
+        game.total_pool -= deposit;
+        refunded += deposit;
+    }
+
+    escrow.check(game)?;
+    Ok(refunded)
+}
@@ -0,0 +1,178 @@
+use anchor_lang::prelude::*;
+use crate::escrow::Escrow;
+use crate::instructions::finalize_game::GameCompleted;
+use crate::instructions::reveal_seed::roll_game_seed;
+use crate::referral::credit_referrals;
+use crate::state::*;
+
+// Completes the game and splits the pool three ways: the platform fee moves from the
+// escrow into the treasury (less the jackpot's and referrers' slices), the creator rake
+// stays in the escrow until the creator claims it, and the rest is paid out to ranked
+// players. Every path that finishes a game goes through here, so each game's fee is
+// collected exactly once.
+// Roll-off games with a tie start another round instead and settle once it resolves, or
+// split the tie once GameAccount::MAX_ROLL_OFFS roll-offs have been played.
+pub fn settle_game<'info>(
+    game: &mut Account<'info, GameAccount>,
+    game_key: &Pubkey,
+    escrow: &Escrow<'_, 'info>,
+    treasury: &mut Account<'info, Treasury>,
+    jackpot: &mut Account<'info, Jackpot>,
+    referral_accounts: &[AccountInfo<'info>],
+    now: i64,
+) -> Result<()> {
+    loop {
+        let (tied, tied_roll) = game.find_winners();
+        if game.tie_break != TieBreak::RollOff
+            || tied.len() < 2
+            || game.round >= GameAccount::MAX_ROLL_OFFS
+        {
+            break;
+        }
+
+        game.start_roll_off(&tied, now)?;
+
+        emit!(RollOffStarted {
+            game_id: game.game_id,
+            round: game.round,
+            players: tied.iter().map(|&i| game.players[i]).collect(),
+            tied_roll,
+        });
+
+        if game.randomness != RandomnessSource::Collaborative {
+            // Tied players roll again through the game's usual roll instruction
+            return Ok(());
+        }
+        // Collaborative rolls need no further input, so the round is derived right away
+        roll_game_seed(game, game_key, now)?;
+    }
+
+    let fee = game.platform_fee();
+    let creator_rake = game.creator_rake_amount();
+    let prize_pool = game.pot() - fee - creator_rake;
+    game.creator_rake = creator_rake;
+    let (winner_indices, highest_roll, dust) = game.complete(prize_pool, now);
+
+    game.fee_collected = fee;
+    treasury.games_settled += 1;
+
+    // Token games keep the fee and dust in their vault for collect_token_fees. The jackpot
+    // and referral rewards are paid in lamports, so only lamport games feed them
+    if game.mint.is_some() {
+        emit!(FeeCollected {
+            game_id: game.game_id,
+            amount: fee,
+            dust,
+            jackpot_contribution: 0,
+            total_accrued: treasury.total_accrued,
+        });
+    } else {
+        // Rounding dust from splitting the prize goes to the treasury with the fee
+        let to_jackpot = game.jackpot_contribution(fee);
+        let to_referrers = credit_referrals(game, escrow, fee, referral_accounts)?;
+        let to_treasury = fee - to_jackpot - to_referrers + dust;
+        escrow.withdraw(game, &treasury.to_account_info(), to_treasury)?;
+        escrow.withdraw(game, &jackpot.to_account_info(), to_jackpot)?;
+
+        treasury.total_accrued += to_treasury;
+        jackpot.balance += to_jackpot;
+        jackpot.total_contributed += to_jackpot;
+
+        emit!(FeeCollected {
+            game_id: game.game_id,
+            amount: fee,
+            dust,
+            jackpot_contribution: to_jackpot,
+            total_accrued: treasury.total_accrued,
+        });
+
+        pay_jackpot(game, escrow, jackpot)?;
+    }
+    escrow.check(game)?;
+
+    emit!(GameCompleted {
+        game_id: game.game_id,
+        winner: game.players[winner_indices[0]],
+        prize_amount: prize_pool - dust,
+        creator_rake,
+        platform_fee: fee,
+        winner_roll: highest_roll,
+        winners: winner_indices.iter().map(|&i| game.players[i]).collect(),
+        round: game.round,
+        payouts: game.payouts.clone(),
+    });
+
+    Ok(())
+}
+
+// Splits the whole jackpot between the seats that hit the game's pattern. The shares are
+// moved into the game's escrow and added to those players' payouts for claim_prize.
+fn pay_jackpot(
+    game: &mut Account<GameAccount>,
+    escrow: &Escrow,
+    jackpot: &mut Account<Jackpot>,
+) -> Result<()> {
+    let winners = game.jackpot_winners();
+    if winners.is_empty() || jackpot.balance == 0 {
+        return Ok(());
+    }
+
+    let share = jackpot.balance / winners.len() as u64;
+    let paid = share * winners.len() as u64;
+    **jackpot.to_account_info().try_borrow_mut_lamports()? -= paid;
+    **escrow.account.to_account_info().try_borrow_mut_lamports()? += paid;
+
+    jackpot.balance -= paid;
+    jackpot.total_paid += paid;
+    jackpot.wins += 1;
+
+    let players: Vec<Pubkey> = winners.iter().map(|&i| game.players[i]).collect();
+    for player in &players {
+        match game.payouts.iter_mut().find(|p| p.player == *player) {
+            Some(payout) => payout.jackpot = share,
+            None => game.payouts.push(Payout {
+                player: *player,
+                rank: 0,
+                amount: 0,
+                jackpot: share,
+                claimed: false,
+            }),
+        }
+    }
+
+    emit!(JackpotWon {
+        game_id: game.game_id,
+        trigger: game.jackpot_trigger,
+        winners: players,
+        share,
+        remaining: jackpot.balance,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct JackpotWon {
+    pub game_id: u64,
+    pub trigger: JackpotTrigger,
+    pub winners: Vec<Pubkey>,
+    pub share: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct RollOffStarted {
+    pub game_id: u64,
+    pub round: u8,
+    pub players: Vec<Pubkey>,
+    pub tied_roll: u8,
+}
+
+#[event]
+pub struct FeeCollected {
+    pub game_id: u64,
+    pub amount: u64,
+    pub dust: u64,
+    pub jackpot_contribution: u64,
+    pub total_accrued: u64,
+}
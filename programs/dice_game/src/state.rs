@@ -1,58 +1,1255 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use dice_entropy::{verify_roll, EntropyVersion, RollInputs};
+use crate::constants::{MAX_CREATOR_RAKE_BPS, MAX_FEE_BPS};
+use crate::error::ErrorCode;
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
 pub enum GameStatus {
+    #[default]
     Waiting,
     Playing,
     Completed,
     Cancelled,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum RandomnessSource {
+    // Dice hashed from the most recent SlotHashes entry, game, player and roll index
+    #[default]
+    SlotHashes,
+    // Player commits to a secret, then reveals it against a later slot hash
+    CommitReveal,
+    // roll_dice opens a RandomnessRequest that the configured oracle fulfils
+    Oracle,
+    // Every player commits a seed when joining; all dice come from the combined reveals
+    Collaborative,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum TieBreak {
+    // Tied winners share the prize
+    #[default]
+    Split,
+    // Tied players roll again in a new round until one is highest
+    RollOff,
+}
+
+// Roll pattern that pays out the jackpot when a game settles
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum JackpotTrigger {
+    #[default]
+    Disabled,
+    // Every player in the game rolled double six
+    AllDoubleSixes,
+    // Anyone rolled double six in a full six-player game
+    DoubleSixFullTable,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct RollCommitment {
+    pub commitment: [u8; 32],
+    pub committed_slot: u64,
+    pub reveal_deadline: i64,
+}
+
+// Inputs a roll was derived from, kept so anyone can re-derive it with dice_entropy::verify_roll
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct RollEntropy {
+    pub version: u8,
+    pub slot: u64,
+    pub slot_hash: [u8; 32],
+    pub seed: [u8; 32],
+    pub nonce: u64,
+}
+
+impl RollEntropy {
+    pub fn inputs(&self, game: &Pubkey, player: &Pubkey) -> Option<RollInputs> {
+        Some(RollInputs {
+            version: EntropyVersion::try_from(self.version).ok()?,
+            game: game.to_bytes(),
+            player: player.to_bytes(),
+            slot: self.slot,
+            slot_hash: self.slot_hash,
+            seed: self.seed,
+            nonce: self.nonce,
+        })
+    }
+}
+
+// What one ranked player is owed from a completed game
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct Payout {
+    pub player: Pubkey,
+    // 1 for the winner; tied players share a rank. 0 for a jackpot-only payout
+    pub rank: u8,
+    pub amount: u64,
+    // Jackpot share won in this game, paid with the prize
+    pub jackpot: u64,
+    pub claimed: bool,
+}
+
+// A game's lifecycle windows in seconds: how long it takes players after creation, how
+// long each round waits for rolls, and how long payouts stay claimable after settlement
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Deadlines {
+    pub join_window: i64,
+    pub roll_window: i64,
+    pub claim_window: i64,
+}
+
+impl Deadlines {
+    pub const SPACE: usize = 8 + 8 + 8;
+
+    pub fn within(&self, min: &Deadlines, max: &Deadlines) -> bool {
+        (min.join_window..=max.join_window).contains(&self.join_window)
+            && (min.roll_window..=max.roll_window).contains(&self.roll_window)
+            && (min.claim_window..=max.claim_window).contains(&self.claim_window)
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
 pub struct DiceRoll {
     pub dice1: u8,
     pub dice2: u8,
     pub total: u8,
     pub rolled_at: i64,
+    pub round: u8,
+    pub entropy: RollEntropy,
+}
+
+// A roll in a game's history, with the seat that made it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct SeatRoll {
+    pub seat: u8,
+    pub roll: DiceRoll,
+}
+
+#[account]
+pub struct ProgramConfig {
+    pub admin: Pubkey,
+    pub oracle: Pubkey,
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+    pub min_entry_fee: u64,
+    pub max_entry_fee: u64,
+    pub max_players: u8,
+    pub max_creator_rake_bps: u16,
+    pub jackpot_bps: u16,
+    pub jackpot_trigger: JackpotTrigger,
+    pub referral_bps: u16,
+    pub stale_game_timeout: i64,
+    pub min_deadlines: Deadlines,
+    pub max_deadlines: Deadlines,
+    pub bump: u8,
+}
+
+impl ProgramConfig {
+    pub fn space() -> usize {
+        8 + // discriminator
+        32 + // admin
+        32 + // oracle
+        2 + // fee_bps
+        32 + // treasury
+        8 + // min_entry_fee
+        8 + // max_entry_fee
+        1 + // max_players
+        2 + // max_creator_rake_bps
+        2 + // jackpot_bps
+        1 + // jackpot_trigger enum
+        2 + // referral_bps
+        8 + // stale_game_timeout
+        Deadlines::SPACE + // min_deadlines
+        Deadlines::SPACE + // max_deadlines
+        1 // bump
+    }
+
+    pub fn apply(&mut self, params: &ConfigParams) -> Result<()> {
+        require!(params.fee_bps <= MAX_FEE_BPS, ErrorCode::InvalidFeeBps);
+        require!(
+            params.max_creator_rake_bps <= MAX_CREATOR_RAKE_BPS,
+            ErrorCode::InvalidCreatorRakeBps
+        );
+        require!(params.jackpot_bps <= 10_000, ErrorCode::InvalidJackpotBps);
+        require!(
+            params.jackpot_bps as u32 + params.referral_bps as u32 <= 10_000,
+            ErrorCode::InvalidReferralBps
+        );
+        require!(
+            params.min_entry_fee > 0 && params.min_entry_fee <= params.max_entry_fee,
+            ErrorCode::InvalidEntryFeeRange
+        );
+        require!(
+            (2..=GameAccount::MAX_PLAYERS as u8).contains(&params.max_players),
+            ErrorCode::InvalidMaxPlayers
+        );
+        let (min, max) = (params.min_deadlines, params.max_deadlines);
+        require!(
+            min.join_window > 0
+                && min.roll_window > 0
+                && min.claim_window > 0
+                && min.within(&min, &max),
+            ErrorCode::InvalidDeadlineBounds
+        );
+        require!(
+            params.stale_game_timeout > max.roll_window,
+            ErrorCode::InvalidStaleGameTimeout
+        );
+
+        self.oracle = params.oracle;
+        self.fee_bps = params.fee_bps;
+        self.treasury = params.treasury;
+        self.min_entry_fee = params.min_entry_fee;
+        self.max_entry_fee = params.max_entry_fee;
+        self.max_players = params.max_players;
+        self.max_creator_rake_bps = params.max_creator_rake_bps;
+        self.jackpot_bps = params.jackpot_bps;
+        self.jackpot_trigger = params.jackpot_trigger;
+        self.referral_bps = params.referral_bps;
+        self.stale_game_timeout = params.stale_game_timeout;
+        self.min_deadlines = params.min_deadlines;
+        self.max_deadlines = params.max_deadlines;
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct ConfigParams {
+    pub oracle: Pubkey,
+    pub fee_bps: u16,
+    // Wallet withdraw_treasury pays out to; fees accrue in the treasury PDA
+    pub treasury: Pubkey,
+    pub min_entry_fee: u64,
+    pub max_entry_fee: u64,
+    pub max_players: u8,
+    // Highest rake a creator may set on their own game
+    pub max_creator_rake_bps: u16,
+    // Share of each game's platform fee routed to the jackpot
+    pub jackpot_bps: u16,
+    pub jackpot_trigger: JackpotTrigger,
+    // Share of a referred player's part of the platform fee that accrues to their referrer
+    pub referral_bps: u16,
+    // Seconds after a game starts, and after its current roll deadline, before
+    // abort_stale_game may cancel and refund it. Must leave room for the longest roll window
+    pub stale_game_timeout: i64,
+    // Range each of a game's deadline windows must fall in at create_game
+    pub min_deadlines: Deadlines,
+    pub max_deadlines: Deadlines,
+}
+
+// Holds collected platform fees. Only accrued fees can be withdrawn, never its rent
+#[account]
+pub struct Treasury {
+    pub total_accrued: u64,
+    pub total_withdrawn: u64,
+    pub games_settled: u64,
+    // Count of create_game calls, handed out as each game's nonce
+    pub games_created: u64,
+    pub bump: u8,
+}
+
+impl Treasury {
+    pub fn space() -> usize {
+        8 + // discriminator
+        8 + // total_accrued
+        8 + // total_withdrawn
+        8 + // games_settled
+        8 + // games_created
+        1 // bump
+    }
+
+    pub fn available(&self) -> u64 {
+        self.total_accrued - self.total_withdrawn
+    }
+}
+
+// Progressive jackpot funded from game fees. balance excludes the account's rent
+#[account]
+pub struct Jackpot {
+    pub balance: u64,
+    pub total_contributed: u64,
+    pub total_paid: u64,
+    pub wins: u64,
+    pub bump: u8,
+}
+
+impl Jackpot {
+    pub fn space() -> usize {
+        8 + // discriminator
+        8 + // balance
+        8 + // total_contributed
+        8 + // total_paid
+        8 + // wins
+        1 // bump
+    }
 }
 
+// Links a player to the referrer they first named, and holds what that referrer has
+// earned from the player's games until claim_referral_rewards pays it out
 #[account]
+pub struct Referral {
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+    pub accrued: u64,
+    pub claimed: u64,
+    pub bump: u8,
+}
+
+impl Referral {
+    pub fn space() -> usize {
+        8 + // discriminator
+        32 + // player
+        32 + // referrer
+        8 + // accrued
+        8 + // claimed
+        1 // bump
+    }
+
+    pub fn unclaimed(&self) -> u64 {
+        self.accrued - self.claimed
+    }
+}
+
+// One sponsor's contribution to one game, refunded to them if the game is cancelled
+#[account]
+pub struct Sponsorship {
+    pub game: Pubkey,
+    // Tells this game apart from an earlier, closed game with the same game_id
+    pub game_nonce: u64,
+    pub sponsor: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl Sponsorship {
+    pub fn space() -> usize {
+        8 + // discriminator
+        32 + // game
+        8 + // game_nonce
+        32 + // sponsor
+        8 + // amount
+        1 // bump
+    }
+}
+
+// A spectator's parimutuel bet on one player of a game
+#[account]
+pub struct SideBet {
+    pub game: Pubkey,
+    pub bettor: Pubkey,
+    pub pick: Pubkey,
+    // Seat the pick held when the bet was placed
+    pub seat_id: u32,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl SideBet {
+    pub fn space() -> usize {
+        8 + // discriminator
+        32 + // game
+        32 + // bettor
+        32 + // pick
+        4 + // seat_id
+        8 + // amount
+        1 // bump
+    }
+}
+
+#[account]
+pub struct RandomnessRequest {
+    pub game: Pubkey,
+    // Nonce of the game the request was made in, as the game_id may be reused
+    pub game_nonce: u64,
+    pub player: Pubkey,
+    pub player_index: u8,
+    pub round: u8,
+    pub requested_slot: u64,
+    pub requested_at: i64,
+    pub fulfilled: bool,
+    pub randomness: [u8; 32],
+    pub bump: u8,
+}
+
+impl RandomnessRequest {
+    pub fn space() -> usize {
+        8 + // discriminator
+        32 + // game
+        8 + // game_nonce
+        32 + // player
+        1 + // player_index
+        1 + // round
+        8 + // requested_slot
+        8 + // requested_at
+        1 + // fulfilled
+        32 + // randomness
+        1 // bump
+    }
+}
+
+#[account]
+#[derive(Default)]
 pub struct GameAccount {
     pub game_id: u64,
     pub creator: Pubkey,
+    // Who paid the game's rent and the escrow's reserve, and gets them back at close_game.
+    // Stays the original creator when the creator role is handed over
+    pub rent_payer: Pubkey,
     pub entry_fee: u64,
     pub max_players: u8,
+    // Players needed for anyone to start the game once joining closes; with fewer the
+    // players can withdraw instead
+    pub min_players: u8,
     pub current_players: u8,
     pub total_pool: u64,
     pub status: GameStatus,
     pub created_at: i64,
+    // Unique per game, unlike game_id which can be reused once a game is closed
+    pub nonce: u64,
     pub started_at: Option<i64>,
     pub completed_at: Option<i64>,
     pub players: Vec<Pubkey>,
+    // Each seat's roll in the last round it played; a roll-off clears its seats' entries
     pub rolls: Vec<Option<DiceRoll>>,
+    // Every roll made in the game, in order, so earlier rounds stay verifiable. Players
+    // are ranked from it
+    pub roll_history: Vec<SeatRoll>,
     pub winner: Option<Pubkey>,
     pub prize_claimed: bool,
+    pub randomness: RandomnessSource,
+    pub commitments: Vec<Option<RollCommitment>>,
+    pub seeds: Vec<Option<[u8; 32]>>,
+    pub fee_bps: u16,
+    pub fee_collected: u64,
+    pub payouts: Vec<Payout>,
+    pub payout_dust: u64,
+    pub tie_break: TieBreak,
+    // Current round; above zero only while a roll-off is running
+    pub round: u8,
+    // Seats taking part in the current round
+    pub in_round: Vec<bool>,
+    // Windows chosen at create_game, and the deadlines derived from them: joining closes
+    // at join_deadline, each round's rolls at roll_deadline, and claims at claim_deadline
+    pub deadlines: Deadlines,
+    pub join_deadline: i64,
+    pub roll_deadline: i64,
+    pub claim_deadline: i64,
+    // Seats forfeited for missing the roll deadline
+    pub forfeited: Vec<bool>,
+    // Share of the prize pool per finishing position, in basis points summing to 10_000
+    pub payout_tiers: Vec<u16>,
+    // Creator's cut of the pool, set at create_game and claimed through claim_prize
+    pub creator_rake_bps: u16,
+    pub creator_rake: u64,
+    pub rake_claimed: bool,
+    // Jackpot rules in force when the game was created
+    pub jackpot_bps: u16,
+    pub jackpot_trigger: JackpotTrigger,
+    // Referrer of each seat at the time it joined, and the referral share in force
+    pub referrers: Vec<Option<Pubkey>>,
+    pub referral_bps: u16,
+    // Lamports added by sponsor_game, kept out of total_pool so refunds can tell them apart
+    pub sponsor_pool: u64,
+    // Spectator side bets: stake not yet settled, and the total backing each seat
+    pub side_pool: u64,
+    pub side_stakes: Vec<u64>,
+    // Side bet lamports still in the escrow: stakes in, payouts out. Once every bet has
+    // settled only rounding dust is left, which close_game sweeps to the treasury
+    pub side_held: u64,
+    // Id of each seat, never reused within a game. Side bets name the seat rather than the
+    // player, so a bet on a player who left doesn't follow them to a new seat
+    pub seat_ids: Vec<u32>,
+    pub next_seat_id: u32,
+    pub bump: u8,
+    // SPL mint for token games, whose stakes sit in the ["vault", game] token account.
+    // Their fee stays in the vault until collect_token_fees sends it to the treasury wallet
+    pub mint: Option<Pubkey>,
+    pub token_fees_collected: bool,
+    // What each seat actually paid in; refunds return it
+    pub deposits: Vec<u64>,
+    // Lamport stakes sit in the ["escrow", game] system account, never in this one
+    pub escrow_bump: u8,
 }
 
 impl GameAccount {
     pub const MAX_PLAYERS: usize = 6;
+    // A tie still standing after this many roll-offs is split
+    pub const MAX_ROLL_OFFS: u8 = 3;
+    pub const MAX_ROLL_HISTORY: usize = Self::MAX_PLAYERS * (Self::MAX_ROLL_OFFS as usize + 1);
 
     // Calculate the size of the account
     pub fn space() -> usize {
         8 + // discriminator
         8 + // game_id
         32 + // creator
+        32 + // rent_payer
         8 + // entry_fee
         1 + // max_players
+        1 + // min_players
         1 + // current_players
         8 + // total_pool
         1 + // status enum
         8 + // created_at
+        8 + // nonce
         1 + 8 + // started_at Option
         1 + 8 + // completed_at Option
         4 + (32 * Self::MAX_PLAYERS) + // players Vec
-        4 + (Self::MAX_PLAYERS * (1 + 20)) + // rolls Vec with Option<DiceRoll>
+        4 + (Self::MAX_PLAYERS * (1 + 12 + 81)) + // rolls Vec with Option<DiceRoll>
+        4 + (Self::MAX_ROLL_HISTORY * (1 + 12 + 81)) + // roll_history Vec<SeatRoll>
         1 + 32 + // winner Option<Pubkey>
-        1 // prize_claimed bool
+        1 + // prize_claimed bool
+        1 + // randomness enum
+        4 + (Self::MAX_PLAYERS * (1 + 32 + 8 + 8)) + // commitments Vec with Option<RollCommitment>
+        4 + (Self::MAX_PLAYERS * (1 + 32)) + // seeds Vec with Option<[u8; 32]>
+        2 + // fee_bps
+        8 + // fee_collected
+        4 + (Self::MAX_PLAYERS * (32 + 1 + 8 + 8 + 1)) + // payouts Vec<Payout>
+        8 + // payout_dust
+        1 + // tie_break enum
+        1 + // round
+        4 + Self::MAX_PLAYERS + // in_round Vec<bool>
+        Deadlines::SPACE + // deadlines
+        8 + // join_deadline
+        8 + // roll_deadline
+        8 + // claim_deadline
+        4 + Self::MAX_PLAYERS + // forfeited Vec<bool>
+        4 + (Self::MAX_PLAYERS * 2) + // payout_tiers Vec<u16>
+        2 + // creator_rake_bps
+        8 + // creator_rake
+        1 + // rake_claimed
+        2 + // jackpot_bps
+        1 + // jackpot_trigger enum
+        4 + (Self::MAX_PLAYERS * (1 + 32)) + // referrers Vec with Option<Pubkey>
+        2 + // referral_bps
+        8 + // sponsor_pool
+        8 + // side_pool
+        4 + (Self::MAX_PLAYERS * 8) + // side_stakes Vec<u64>
+        8 + // side_held
+        4 + (Self::MAX_PLAYERS * 4) + // seat_ids Vec<u32>
+        4 + // next_seat_id
+        1 + // bump
+        1 + 32 + // mint Option<Pubkey>
+        1 + // token_fees_collected
+        4 + (Self::MAX_PLAYERS * 8) + // deposits Vec<u64>
+        1 // escrow_bump
+    }
+
+    // A payout table needs one to max_players non-increasing, non-zero tiers
+    // that add up to the whole prize pool
+    pub fn valid_payout_tiers(tiers: &[u16], max_players: u8) -> bool {
+        !tiers.is_empty()
+            && tiers.len() <= max_players as usize
+            && tiers.iter().all(|&bps| bps > 0)
+            && tiers.windows(2).all(|pair| pair[0] >= pair[1])
+            && tiers.iter().map(|&bps| bps as u32).sum::<u32>() == 10_000
+    }
+
+    // Unique per seat and round, mixed into roll entropy
+    pub fn roll_index(&self, seat: usize) -> u64 {
+        self.round as u64 * Self::MAX_PLAYERS as u64 + seat as u64
+    }
+
+    // Roll-off: only the tied seats play the next round, with fresh rolls and commitments.
+    // Their earlier rolls stay in roll_history
+    pub fn start_roll_off(&mut self, tied: &[usize], now: i64) -> Result<()> {
+        require!(self.round < Self::MAX_ROLL_OFFS, ErrorCode::TooManyRounds);
+        self.round += 1;
+        self.roll_deadline = now + self.deadlines.roll_window;
+
+        for (i, in_round) in self.in_round.iter_mut().enumerate() {
+            *in_round = tied.contains(&i);
+        }
+        for &i in tied {
+            self.rolls[i] = None;
+            if self.randomness == RandomnessSource::CommitReveal {
+                self.commitments[i] = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Everything the game pays out from: entry fees plus sponsor contributions
+    pub fn pot(&self) -> u64 {
+        self.total_pool + self.sponsor_pool
+    }
+
+    // Freerolls charge no entry; their pot comes entirely from sponsors
+    pub fn is_freeroll(&self) -> bool {
+        self.entry_fee == 0
+    }
+
+    // Platform fee on the pot, at the rate the config had when the game was created
+    pub fn platform_fee(&self) -> u64 {
+        (self.pot() as u128 * self.fee_bps as u128 / 10_000) as u64
+    }
+
+    // Part of the platform fee that goes to the jackpot instead of the treasury
+    pub fn jackpot_contribution(&self, fee: u64) -> u64 {
+        (fee as u128 * self.jackpot_bps as u128 / 10_000) as u64
+    }
+
+    // Seats that hit the game's jackpot pattern, judged on the first round's rolls. A
+    // double six is the top total, so under RollOff it usually leads to a roll-off; the
+    // rolls that hit the pattern are kept in roll_history and still pay at settlement
+    pub fn jackpot_winners(&self) -> Vec<usize> {
+        let players = self.current_players as usize;
+        let sixes: Vec<usize> = (0..players)
+            .filter(|&i| {
+                self.roll_history.iter().any(|r| {
+                    r.seat as usize == i
+                        && r.roll.round == 0
+                        && r.roll.dice1 == 6
+                        && r.roll.dice2 == 6
+                })
+            })
+            .collect();
+
+        match self.jackpot_trigger {
+            JackpotTrigger::Disabled => Vec::new(),
+            JackpotTrigger::AllDoubleSixes if sixes.len() == players => sixes,
+            JackpotTrigger::DoubleSixFullTable if players == Self::MAX_PLAYERS => sixes,
+            _ => Vec::new(),
+        }
     }
-}
\ No newline at end of file
+
+    pub fn creator_rake_amount(&self) -> u64 {
+        (self.pot() as u128 * self.creator_rake_bps as u128 / 10_000) as u64
+    }
+
+    // True once every payout and the creator rake have been paid
+    pub fn all_claimed(&self) -> bool {
+        self.payouts.iter().all(|p| p.claimed) && (self.rake_claimed || self.creator_rake == 0)
+    }
+
+    // Lamports the escrow owes out, above its rent reserve. Until settlement that is the
+    // whole pot; afterwards the unclaimed payouts and rake, the fee having left at
+    // settlement. Token games escrow only side bets.
+    pub fn escrow_liabilities(&self) -> u64 {
+        let stakes = match (self.mint, self.status) {
+            (Some(_), _) => 0,
+            (None, GameStatus::Completed) => {
+                let payouts: u64 = self.payouts
+                    .iter()
+                    .filter(|p| !p.claimed)
+                    .map(|p| p.amount + p.jackpot)
+                    .sum();
+                let rake = if self.rake_claimed { 0 } else { self.creator_rake };
+                payouts + rake
+            }
+            (None, _) => self.pot(),
+        };
+        stakes + self.side_held
+    }
+
+    // Moves the game to Playing. Collaborative seeds are due by the first roll deadline
+    pub fn start(&mut self, now: i64) {
+        self.status = GameStatus::Playing;
+        self.started_at = Some(now);
+        self.roll_deadline = now + self.deadlines.roll_window;
+
+        if self.randomness == RandomnessSource::Collaborative {
+            for commitment in self.commitments.iter_mut().flatten() {
+                commitment.reveal_deadline = self.roll_deadline;
+            }
+        }
+    }
+
+    // Removes a seat, shifting the per-seat vectors so they stay aligned with players
+    pub fn remove_player(&mut self, index: usize) {
+        self.players.remove(index);
+        self.rolls.remove(index);
+        self.rolls.push(None);
+        self.commitments.remove(index);
+        self.commitments.push(None);
+        self.seeds.remove(index);
+        self.seeds.push(None);
+        self.in_round.remove(index);
+        self.in_round.push(true);
+        self.forfeited.remove(index);
+        self.forfeited.push(false);
+        self.referrers.remove(index);
+        self.referrers.push(None);
+        // The seat's side stakes leave the pool with it; its bets no longer match any
+        // seat id and are refunded when they settle
+        self.side_stakes.remove(index);
+        self.side_stakes.push(0);
+        self.seat_ids.remove(index);
+        self.seat_ids.push(0);
+        self.deposits.remove(index);
+        self.deposits.push(0);
+    }
+
+    // A player leaving before the start frees their seat and takes their deposit out of
+    // the pool. A leaving creator hands the role to the next player in seat order but keeps
+    // the rent they paid, returned at close_game; once nobody is left the game is cancelled.
+    pub fn leave(&mut self, index: usize, now: i64) {
+        let deposit = self.deposits[index];
+        let was_creator = self.players[index] == self.creator;
+
+        self.remove_player(index);
+        self.current_players -= 1;
+        self.total_pool -= deposit;
+
+        if self.players.is_empty() {
+            self.status = GameStatus::Cancelled;
+            self.completed_at = Some(now);
+        } else if was_creator {
+            self.creator = self.players[index % self.players.len()];
+        }
+    }
+
+    // What a side bet returns once the game is over. Bets on the winners (every seat
+    // finishing first, so all tied winners under a split) share the stakes backing seated
+    // players pro rata; bets are refunded when the game was cancelled, their seat was given
+    // up, or nobody backed a winner.
+    pub fn side_bet_payout(&self, bet: &SideBet) -> u64 {
+        let seat = self.seat_ids
+            .iter()
+            .take(self.current_players as usize)
+            .position(|&id| id == bet.seat_id);
+        let (Some(seat), GameStatus::Completed) = (seat, self.status) else {
+            return bet.amount;
+        };
+
+        let winners = self.rank_players().into_iter().next().unwrap_or_default();
+        let winning_stake: u64 = winners.iter().map(|&w| self.side_stakes[w]).sum();
+        if winning_stake == 0 {
+            return bet.amount;
+        }
+        if !winners.contains(&seat) {
+            return 0;
+        }
+
+        let pool: u64 = self.side_stakes.iter().sum();
+        (bet.amount as u128 * pool as u128 / winning_stake as u128) as u64
+    }
+
+    // Collaborative games: true once every seat has revealed its seed
+    pub fn all_seeds_revealed(&self) -> bool {
+        self.seeds.iter().take(self.current_players as usize).all(|s| s.is_some())
+    }
+
+    // Combines every revealed seed into one game seed and rolls each revealing player in
+    // the current round from it. Seats that never revealed get no roll and forfeit.
+    // Returns the game seed.
+    pub fn roll_from_seeds(&mut self, game_key: &Pubkey, now: i64) -> Result<[u8; 32]> {
+        let players = self.current_players as usize;
+        let mut parts: Vec<&[u8]> = vec![game_key.as_ref()];
+        parts.extend(self.seeds.iter().take(players).flatten().map(|s| s.as_ref()));
+        let game_seed = hashv(&parts).to_bytes();
+
+        for i in 0..players {
+            if self.seeds[i].is_some() && self.in_round[i] {
+                let entropy = RollEntropy {
+                    version: EntropyVersion::Sha256V1 as u8,
+                    seed: game_seed,
+                    nonce: self.roll_index(i),
+                    ..Default::default()
+                };
+                self.record_roll(game_key, i, entropy, now)?;
+            }
+        }
+
+        Ok(game_seed)
+    }
+
+    // A player who committed but let the reveal deadline pass loses their seat, as does
+    // one forfeited by forfeit_idle_players
+    pub fn has_forfeited(&self, index: usize, now: i64) -> bool {
+        self.forfeited[index]
+            || (self.rolls[index].is_none()
+                && matches!(self.commitments[index], Some(c) if now > c.reveal_deadline))
+    }
+
+    // Seats of the current round still without a roll once its roll deadline has passed
+    pub fn idle_players(&self, now: i64) -> Vec<usize> {
+        if now <= self.roll_deadline {
+            return Vec::new();
+        }
+        (0..self.current_players as usize)
+            .filter(|&i| self.in_round[i] && self.rolls[i].is_none())
+            .collect()
+    }
+
+    // True once every player in the round has either rolled or forfeited, and there is a
+    // roll to rank. That can be from an earlier round when every seat in a roll-off forfeited
+    pub fn all_rolled(&self, now: i64) -> bool {
+        let players = self.current_players as usize;
+        let any_rolled = self.rolls.iter().take(players).any(|r| r.is_some());
+        let all_done = (0..players)
+            .filter(|&i| self.in_round[i])
+            .all(|i| self.rolls[i].is_some() || self.has_forfeited(i, now));
+        any_rolled && all_done
+    }
+
+    // Highest roll in the current round wins and ties share the win; forfeited seats have
+    // no roll and are skipped. When every seat in a roll-off forfeited, the best of the
+    // earlier rounds' finishers win instead
+    pub fn find_winners(&self) -> (Vec<usize>, u8) {
+        let mut winner_indices = Vec::new();
+        let mut highest_roll = 0u8;
+
+        for (i, roll_opt) in self.rolls.iter().enumerate().take(self.current_players as usize) {
+            if !self.in_round[i] {
+                continue;
+            }
+            if let Some(roll) = roll_opt {
+                if roll.total > highest_roll {
+                    highest_roll = roll.total;
+                    winner_indices.clear();
+                    winner_indices.push(i);
+                } else if roll.total == highest_roll {
+                    winner_indices.push(i);
+                }
+            }
+        }
+
+        if winner_indices.is_empty() {
+            if let Some(best) = self.rank_players().into_iter().next() {
+                highest_roll = self.final_roll(best[0]).map_or(0, |r| r.total);
+                winner_indices = best;
+            }
+        }
+
+        (winner_indices, highest_roll)
+    }
+
+    // Derives a player's roll from its entropy with the shared verify_roll, so the
+    // stored dice always match what an auditor re-deriving them gets
+    pub fn record_roll(
+        &mut self,
+        game_key: &Pubkey,
+        index: usize,
+        entropy: RollEntropy,
+        now: i64,
+    ) -> Result<DiceRoll> {
+        let inputs = entropy
+            .inputs(game_key, &self.players[index])
+            .ok_or(ErrorCode::UnknownEntropyVersion)?;
+        let outcome = verify_roll(&inputs);
+
+        let roll = DiceRoll {
+            dice1: outcome.dice1,
+            dice2: outcome.dice2,
+            total: outcome.total,
+            rolled_at: now,
+            round: self.round,
+            entropy,
+        };
+        self.store_roll(index, roll);
+        Ok(roll)
+    }
+
+    // Sets a seat's roll for the current round and appends it to the history
+    pub fn store_roll(&mut self, index: usize, roll: DiceRoll) {
+        self.rolls[index] = Some(roll);
+        self.roll_history.push(SeatRoll { seat: index as u8, roll });
+    }
+
+    // The roll a seat finishes on: its roll in the last round it played. A seat still in
+    // the current round without a roll there has forfeited and doesn't finish
+    pub fn final_roll(&self, seat: usize) -> Option<DiceRoll> {
+        self.roll_history
+            .iter()
+            .rev()
+            .find(|r| r.seat as usize == seat)
+            .map(|r| r.roll)
+            .filter(|r| !self.in_round[seat] || r.round == self.round)
+    }
+
+    // Seats that rolled, grouped by finishing position, best first. Players knocked out
+    // in a later roll-off round finish above everyone who lost an earlier round.
+    pub fn rank_players(&self) -> Vec<Vec<usize>> {
+        let mut ranked: Vec<(usize, (u8, u8))> = (0..self.current_players as usize)
+            .filter_map(|i| self.final_roll(i).map(|r| (i, (r.round, r.total))))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut last_key = None;
+        for (seat, key) in ranked {
+            match groups.last_mut() {
+                Some(group) if last_key == Some(key) => group.push(seat),
+                _ => groups.push(vec![seat]),
+            }
+            last_key = Some(key);
+        }
+        groups
+    }
+
+    // Marks the game completed and pays `prize_pool` out by the payout table. Players tied
+    // on a position pool the tiers they cover and split them evenly; tiers left over when
+    // fewer players finished than there are tiers go to the winners.
+    // Returns the winning seats, their roll and the rounding dust no player receives.
+    pub fn complete(&mut self, prize_pool: u64, now: i64) -> (Vec<usize>, u8, u64) {
+        let (winner_indices, highest_roll) = self.find_winners();
+        let groups = self.rank_players();
+
+        let mut group_bps: Vec<u32> = Vec::with_capacity(groups.len());
+        let mut position = 0;
+        for group in &groups {
+            let end = (position + group.len()).min(self.payout_tiers.len());
+            let start = position.min(end);
+            group_bps.push(self.payout_tiers[start..end].iter().map(|&bps| bps as u32).sum());
+            position += group.len();
+        }
+        if let Some(first) = group_bps.first_mut() {
+            *first += self.payout_tiers.iter().skip(position).map(|&bps| bps as u32).sum::<u32>();
+        }
+
+        let mut payouts = Vec::new();
+        for (rank, (group, bps)) in groups.iter().zip(group_bps).enumerate() {
+            let amount = (prize_pool as u128 * bps as u128 / 10_000) as u64;
+            let share = amount / group.len() as u64;
+            if share == 0 {
+                continue;
+            }
+            for &seat in group {
+                payouts.push(Payout {
+                    player: self.players[seat],
+                    rank: rank as u8 + 1,
+                    amount: share,
+                    jackpot: 0,
+                    claimed: false,
+                });
+            }
+        }
+        let paid: u64 = payouts.iter().map(|p| p.amount).sum();
+        let dust = prize_pool - paid;
+
+        self.payouts = payouts;
+        self.payout_dust = dust;
+        self.claim_deadline = now + self.deadlines.claim_window;
+
+        // First winner by seat, kept for single-winner readers
+        self.winner = Some(self.players[winner_indices[0]]);
+        self.status = GameStatus::Completed;
+        self.completed_at = Some(now);
+
+        (winner_indices, highest_roll, dust)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_000;
+
+    // A game in play with `players` seats and the given payout table
+    fn game(players: usize, tiers: &[u16]) -> GameAccount {
+        let seats = GameAccount::MAX_PLAYERS;
+        GameAccount {
+            max_players: seats as u8,
+            current_players: players as u8,
+            status: GameStatus::Playing,
+            players: (0..players).map(|_| Pubkey::new_unique()).collect(),
+            rolls: vec![None; seats],
+            in_round: vec![true; seats],
+            forfeited: vec![false; seats],
+            side_stakes: vec![0; seats],
+            seat_ids: (1..=seats as u32).collect(),
+            payout_tiers: tiers.to_vec(),
+            deadlines: Deadlines { claim_window: 60, ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    fn roll(game: &mut GameAccount, seat: usize, total: u8, round: u8) {
+        game.store_roll(seat, DiceRoll {
+            dice1: total / 2,
+            dice2: total - total / 2,
+            total,
+            rolled_at: NOW,
+            round,
+            entropy: RollEntropy::default(),
+        });
+    }
+
+    // (seat, rank, amount) of every payout
+    fn payouts(game: &GameAccount) -> Vec<(usize, u8, u64)> {
+        game.payouts
+            .iter()
+            .map(|p| {
+                let seat = game.players.iter().position(|k| *k == p.player).unwrap();
+                (seat, p.rank, p.amount)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ranks_by_total_with_ties_grouped_in_seat_order() {
+        let mut game = game(4, &[10_000]);
+        roll(&mut game, 0, 7, 0);
+        roll(&mut game, 1, 9, 0);
+        roll(&mut game, 2, 7, 0);
+
+        // Seat 3 never rolled, so it isn't ranked
+        assert_eq!(game.rank_players(), vec![vec![1], vec![0, 2]]);
+    }
+
+    #[test]
+    fn roll_off_survivors_rank_above_earlier_rounds() {
+        let mut game = game(3, &[10_000]);
+        roll(&mut game, 0, 10, 0);
+        roll(&mut game, 1, 10, 0);
+        roll(&mut game, 2, 6, 0);
+        game.start_roll_off(&[0, 1], NOW).unwrap();
+        roll(&mut game, 0, 4, 1);
+        roll(&mut game, 1, 8, 1);
+
+        // Seat 0 lost the roll-off with a 4 but still finishes above seat 2's 6
+        assert_eq!(game.rank_players(), vec![vec![1], vec![0], vec![2]]);
+        assert_eq!(game.find_winners(), (vec![1], 8));
+    }
+
+    #[test]
+    fn roll_offs_keep_every_round_in_the_history() {
+        let mut game = game(2, &[10_000]);
+        roll(&mut game, 0, 7, 0);
+        roll(&mut game, 1, 7, 0);
+        game.start_roll_off(&[0, 1], NOW).unwrap();
+        roll(&mut game, 0, 5, 1);
+        roll(&mut game, 1, 9, 1);
+
+        let history: Vec<(u8, u8, u8)> = game.roll_history
+            .iter()
+            .map(|r| (r.seat, r.roll.round, r.roll.total))
+            .collect();
+        assert_eq!(history, vec![(0, 0, 7), (1, 0, 7), (0, 1, 5), (1, 1, 9)]);
+    }
+
+    #[test]
+    fn seats_that_miss_a_roll_off_do_not_finish() {
+        let mut game = game(3, &[10_000]);
+        roll(&mut game, 0, 10, 0);
+        roll(&mut game, 1, 10, 0);
+        roll(&mut game, 2, 6, 0);
+        game.start_roll_off(&[0, 1], NOW).unwrap();
+        roll(&mut game, 1, 3, 1);
+        game.forfeited[0] = true;
+
+        // Seat 0's round-0 roll is kept but doesn't rank it
+        assert_eq!(game.rank_players(), vec![vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn roll_offs_stop_at_the_cap() {
+        let mut game = game(2, &[10_000]);
+        for _ in 0..GameAccount::MAX_ROLL_OFFS {
+            game.start_roll_off(&[0, 1], NOW).unwrap();
+        }
+
+        assert!(game.start_roll_off(&[0, 1], NOW).is_err());
+    }
+
+    #[test]
+    fn pays_each_position_its_tier() {
+        let mut game = game(3, &[5_000, 3_000, 2_000]);
+        roll(&mut game, 0, 5, 0);
+        roll(&mut game, 1, 11, 0);
+        roll(&mut game, 2, 8, 0);
+
+        let (winners, highest, dust) = game.complete(1_000, NOW);
+
+        assert_eq!((winners, highest, dust), (vec![1], 11, 0));
+        assert_eq!(payouts(&game), vec![(1, 1, 500), (2, 2, 300), (0, 3, 200)]);
+        assert_eq!(game.winner, Some(game.players[1]));
+        assert_eq!(game.status, GameStatus::Completed);
+        assert_eq!(game.completed_at, Some(NOW));
+        assert_eq!(game.claim_deadline, NOW + 60);
+    }
+
+    #[test]
+    fn tied_positions_pool_the_tiers_they_cover() {
+        let mut game = game(3, &[5_000, 3_000, 2_000]);
+        roll(&mut game, 0, 12, 0);
+        roll(&mut game, 1, 4, 0);
+        roll(&mut game, 2, 4, 0);
+
+        game.complete(1_000, NOW);
+
+        // Seats 1 and 2 share second and third: 30% + 20% split evenly
+        assert_eq!(payouts(&game), vec![(0, 1, 500), (1, 2, 250), (2, 2, 250)]);
+        assert_eq!(game.payout_dust, 0);
+    }
+
+    #[test]
+    fn tiers_nobody_finished_in_go_to_the_winners() {
+        let mut game = game(3, &[6_000, 3_000, 1_000]);
+        roll(&mut game, 0, 9, 0);
+        roll(&mut game, 2, 3, 0);
+        game.forfeited[1] = true;
+
+        game.complete(1_000, NOW);
+
+        // Only two finished, so the third tier's 10% goes to the winner
+        assert_eq!(payouts(&game), vec![(0, 1, 700), (2, 2, 300)]);
+        assert_eq!(game.payout_dust, 0);
+    }
+
+    #[test]
+    fn rounding_leftovers_are_dust() {
+        let mut game = game(2, &[5_000, 5_000]);
+        roll(&mut game, 0, 9, 0);
+        roll(&mut game, 1, 3, 0);
+
+        let (_, _, dust) = game.complete(999, NOW);
+
+        assert_eq!(payouts(&game), vec![(0, 1, 499), (1, 2, 499)]);
+        assert_eq!(dust, 1);
+        assert_eq!(game.payout_dust, 1);
+    }
+
+    #[test]
+    fn positions_whose_share_rounds_to_zero_get_no_payout() {
+        let mut game = game(2, &[9_999, 1]);
+        roll(&mut game, 0, 9, 0);
+        roll(&mut game, 1, 3, 0);
+
+        let (_, _, dust) = game.complete(100, NOW);
+
+        assert_eq!(payouts(&game), vec![(0, 1, 99)]);
+        assert_eq!(dust, 1);
+    }
+
+    #[test]
+    fn split_ties_share_first_place_evenly() {
+        let mut game = game(3, &[10_000]);
+        roll(&mut game, 0, 11, 0);
+        roll(&mut game, 1, 5, 0);
+        roll(&mut game, 2, 11, 0);
+
+        let (winners, highest, dust) = game.complete(1_001, NOW);
+
+        assert_eq!((winners, highest), (vec![0, 2], 11));
+        assert_eq!(payouts(&game), vec![(0, 1, 500), (2, 1, 500)]);
+        assert_eq!(dust, 1);
+        // The first tied seat stands in as the single winner
+        assert_eq!(game.winner, Some(game.players[0]));
+    }
+
+    #[test]
+    fn split_ties_below_first_share_their_tiers() {
+        let mut game = game(4, &[4_000, 3_000, 2_000, 1_000]);
+        roll(&mut game, 0, 6, 0);
+        roll(&mut game, 1, 6, 0);
+        roll(&mut game, 2, 6, 0);
+        roll(&mut game, 3, 12, 0);
+
+        game.complete(900, NOW);
+
+        // Three tied for second pool 30% + 20% + 10% and split it three ways
+        assert_eq!(
+            payouts(&game),
+            vec![(3, 1, 360), (0, 2, 180), (1, 2, 180), (2, 2, 180)]
+        );
+    }
+
+    #[test]
+    fn side_bets_on_every_split_winner_are_paid() {
+        let mut game = game(3, &[10_000]);
+        game.side_stakes[0] = 100;
+        game.side_stakes[1] = 600;
+        game.side_stakes[2] = 300;
+        roll(&mut game, 0, 11, 0);
+        roll(&mut game, 1, 5, 0);
+        roll(&mut game, 2, 11, 0);
+        game.complete(1_000, NOW);
+
+        let bet = |seat: usize, amount| SideBet {
+            game: Pubkey::default(),
+            bettor: Pubkey::new_unique(),
+            pick: game.players[seat],
+            seat_id: game.seat_ids[seat],
+            amount,
+            bump: 0,
+        };
+
+        // Backers of seats 0 and 2 share the whole 1_000 pool by stake
+        assert_eq!(game.side_bet_payout(&bet(0, 100)), 250);
+        assert_eq!(game.side_bet_payout(&bet(2, 300)), 750);
+        assert_eq!(game.side_bet_payout(&bet(1, 600)), 0);
+    }
+
+    #[test]
+    fn double_sixes_pay_the_jackpot_after_a_roll_off() {
+        let mut game = game(2, &[10_000]);
+        game.tie_break = TieBreak::RollOff;
+        game.jackpot_trigger = JackpotTrigger::AllDoubleSixes;
+        roll(&mut game, 0, 12, 0);
+        roll(&mut game, 1, 12, 0);
+
+        // Everyone rolling double six is always a tie, so a roll-off follows
+        let (tied, _) = game.find_winners();
+        game.start_roll_off(&tied, NOW).unwrap();
+        roll(&mut game, 0, 5, 1);
+        roll(&mut game, 1, 9, 1);
+        game.complete(1_000, NOW);
+
+        assert_eq!(payouts(&game), vec![(1, 1, 1_000)]);
+        assert_eq!(game.jackpot_winners(), vec![0, 1]);
+    }
+
+    #[test]
+    fn full_table_double_sixes_count_before_the_roll_off() {
+        let mut game = game(6, &[10_000]);
+        game.tie_break = TieBreak::RollOff;
+        game.jackpot_trigger = JackpotTrigger::DoubleSixFullTable;
+        for (seat, total) in [(0, 4), (1, 12), (2, 7), (3, 12), (4, 2), (5, 9)] {
+            roll(&mut game, seat, total, 0);
+        }
+
+        game.start_roll_off(&[1, 3], NOW).unwrap();
+        roll(&mut game, 1, 8, 1);
+        roll(&mut game, 3, 12, 1);
+
+        // Round-1 double sixes don't count: only round 0 is judged
+        assert_eq!(game.jackpot_winners(), vec![1, 3]);
+    }
+
+    #[test]
+    fn creator_rake_is_taken_from_the_whole_pot() {
+        let mut game = game(2, &[10_000]);
+        game.total_pool = 1_000_000;
+        game.sponsor_pool = 333;
+        game.fee_bps = 250;
+        game.creator_rake_bps = 500;
+
+        // Both cuts are on entry fees plus sponsorships, rounded down
+        assert_eq!(game.pot(), 1_000_333);
+        assert_eq!(game.platform_fee(), 25_008);
+        assert_eq!(game.creator_rake_amount(), 50_016);
+
+        game.creator_rake_bps = 0;
+        assert_eq!(game.creator_rake_amount(), 0);
+    }
+
+    #[test]
+    fn prize_pool_is_what_fee_and_rake_leave() {
+        let mut game = game(2, &[10_000]);
+        game.total_pool = 1_000_000;
+        game.fee_bps = 250;
+        game.creator_rake_bps = 500;
+        roll(&mut game, 0, 9, 0);
+        roll(&mut game, 1, 4, 0);
+
+        // As settle_game splits it
+        let fee = game.platform_fee();
+        let rake = game.creator_rake_amount();
+        game.creator_rake = rake;
+        game.complete(game.pot() - fee - rake, NOW);
+
+        assert_eq!(payouts(&game), vec![(0, 1, 925_000)]);
+        assert_eq!(fee + rake + 925_000, game.pot());
+
+        // The rake stays owed from the escrow until the creator claims it
+        assert_eq!(game.escrow_liabilities(), 925_000 + 50_000);
+        game.payouts[0].claimed = true;
+        assert!(!game.all_claimed());
+        game.rake_claimed = true;
+        assert!(game.all_claimed());
+        assert_eq!(game.escrow_liabilities(), 0);
+    }
+}
@@ -0,0 +1,147 @@
+use anchor_lang::prelude::*;
+use anchor_spl::memo::{build_memo, BuildMemo, Memo};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        memo_transfer::memo_required, transfer_fee::TransferFeeAmount,
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+};
+use anchor_spl::token_2022_extensions::transfer_fee::{
+    harvest_withheld_tokens_to_mint, HarvestWithheldTokensToMint,
+};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+use crate::error::ErrorCode;
+use crate::state::GameAccount;
+
+// Token games hold their stakes in a vault token account at ["vault", game], whose
+// authority is the game PDA. Instructions that move stakes take the mint, vault, the
+// user's token account and the token program as optional accounts, required for token
+// games. Both the SPL Token and Token-2022 programs are accepted.
+pub struct TokenAccounts<'a, 'info> {
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub vault: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub user: &'a InterfaceAccount<'info, TokenAccount>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    pub memo_program: Option<&'a Program<'info, Memo>>,
+}
+
+impl<'a, 'info> TokenAccounts<'a, 'info> {
+    pub fn require(
+        mint: &'a Option<InterfaceAccount<'info, Mint>>,
+        vault: &'a mut Option<InterfaceAccount<'info, TokenAccount>>,
+        user: &'a Option<InterfaceAccount<'info, TokenAccount>>,
+        token_program: &'a Option<Interface<'info, TokenInterface>>,
+        memo_program: &'a Option<Program<'info, Memo>>,
+    ) -> Result<Self> {
+        match (mint, vault, user, token_program) {
+            (Some(mint), Some(vault), Some(user), Some(token_program)) => Ok(Self {
+                mint,
+                vault,
+                user,
+                token_program,
+                memo_program: memo_program.as_ref(),
+            }),
+            _ => err!(ErrorCode::MissingTokenAccounts),
+        }
+    }
+
+    // Moves `amount` from the user's account into the vault, signed by the user. Returns
+    // what the vault actually received, which is less when the mint charges a transfer fee
+    pub fn deposit(&mut self, authority: &Signer<'info>, amount: u64) -> Result<u64> {
+        let before = self.vault.amount;
+
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.user.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to: self.vault.to_account_info(),
+                    authority: authority.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint.decimals,
+        )?;
+
+        self.vault.reload()?;
+        Ok(self.vault.amount - before)
+    }
+
+    // Moves `amount` from the vault to the user's account, signed by the game PDA. Any
+    // transfer fee is withheld from what the user receives, never taken from other stakes.
+    pub fn withdraw(&self, game: &Account<'info, GameAccount>, amount: u64) -> Result<()> {
+        // Token-2022 accounts can insist on a memo right before every incoming transfer
+        if self.requires_memo()? {
+            let memo_program = self.memo_program.ok_or(ErrorCode::MissingMemoProgram)?;
+            build_memo(
+                CpiContext::new(memo_program.to_account_info(), BuildMemo {}),
+                format!("dice_game payout {}", game.game_id).as_bytes(),
+            )?;
+        }
+
+        let game_id = game.game_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"game", game_id.as_ref(), &[game.bump]];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.vault.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to: self.user.to_account_info(),
+                    authority: game.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            self.mint.decimals,
+        )
+    }
+
+    // Token-2022 withholds transfer fees in the receiving account, so a transfer-fee vault
+    // keeps part of every deposit. close_account refuses while any is left, so the fees are
+    // moved to the mint, where the mint's withdraw authority collects them
+    pub fn harvest_withheld_fees(&self) -> Result<()> {
+        let info = self.vault.to_account_info();
+        if *info.owner != spl_token_2022::ID {
+            return Ok(());
+        }
+
+        let withheld = {
+            let data = info.try_borrow_data()?;
+            let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+            account
+                .get_extension::<TransferFeeAmount>()
+                .map_or(0, |fees| u64::from(fees.withheld_amount))
+        };
+        if withheld == 0 {
+            return Ok(());
+        }
+
+        harvest_withheld_tokens_to_mint(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                HarvestWithheldTokensToMint {
+                    token_program_id: self.token_program.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                },
+            ),
+            vec![info],
+        )
+    }
+
+    fn requires_memo(&self) -> Result<bool> {
+        let info = self.user.to_account_info();
+        if *info.owner != spl_token_2022::ID {
+            return Ok(false);
+        }
+
+        let data = info.try_borrow_data()?;
+        let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+        Ok(memo_required(&account))
+    }
+}
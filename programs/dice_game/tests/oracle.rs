@@ -0,0 +1,363 @@
+// Runs the oracle request/fulfil flow, and the create and join it starts from, in
+// solana-program-test with the program loaded natively, so it needs no validator or
+// network. The oracle is the committed mock keypair from tests/fixtures, the same one
+// tests/dice-game-oracle.ts uses on localnet.
+
+// The program's own paths into solana_program, like bpf_loader_upgradeable, are deprecated
+#![allow(deprecated)]
+
+use anchor_lang::prelude::{AccountInfo, Pubkey};
+use anchor_lang::solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use dice_game::{
+    ConfigParams, Deadlines, GameAccount, GameStatus, JackpotTrigger, RandomnessSource, Referral,
+    TieBreak,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+const ENTRY_FEE: u64 = LAMPORTS_PER_SOL / 10;
+
+// Anchor's entry wants its accounts to live as long as their data, which the native
+// processor doesn't promise; leaking the slice per instruction is fine in a test
+fn process(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    dice_game::entry(program_id, accounts, data)
+}
+
+fn mock_oracle() -> Keypair {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/fixtures/mock-oracle.json");
+    let json = std::fs::read_to_string(path).unwrap();
+    let bytes: Vec<u8> = json
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|b| b.trim().parse().unwrap())
+        .collect();
+    Keypair::try_from(&bytes[..]).unwrap()
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &dice_game::ID).0
+}
+
+struct Game {
+    ctx: ProgramTestContext,
+    oracle: Keypair,
+    creator: Keypair,
+    opponent: Keypair,
+    game: Pubkey,
+}
+
+impl Game {
+    // Initializes the program with the mock oracle and starts a two-player oracle game,
+    // the opponent joining with `referrer` if given
+    async fn start(referrer: Option<Pubkey>) -> Self {
+        let admin = Keypair::new();
+        let oracle = mock_oracle();
+        let creator = Keypair::new();
+        let opponent = Keypair::new();
+
+        let mut test = ProgramTest::new("dice_game", dice_game::ID, processor!(process));
+        test.prefer_bpf(false);
+        for wallet in [&admin, &creator, &opponent] {
+            test.add_account(
+                wallet.pubkey(),
+                Account::new(2 * LAMPORTS_PER_SOL, 0, &system_program::ID),
+            );
+        }
+        // Loaded natively the program has no ProgramData, so stand one in for initialize
+        test.add_account(
+            Pubkey::find_program_address(&[dice_game::ID.as_ref()], &bpf_loader_upgradeable::ID).0,
+            Account::new_data(
+                LAMPORTS_PER_SOL,
+                &UpgradeableLoaderState::ProgramData {
+                    slot: 0,
+                    upgrade_authority_address: Some(admin.pubkey()),
+                },
+                &bpf_loader_upgradeable::ID,
+            )
+            .unwrap(),
+        );
+        let ctx = test.start_with_context().await;
+
+        let game_id = 1u64;
+        let mut game = Self {
+            ctx,
+            oracle,
+            creator,
+            opponent,
+            game: pda(&[b"game", &game_id.to_le_bytes()]),
+        };
+
+        let deadlines = Deadlines {
+            join_window: 60 * 60,
+            roll_window: 60 * 60,
+            claim_window: 24 * 60 * 60,
+        };
+        let initialize = Instruction {
+            program_id: dice_game::ID,
+            accounts: dice_game::accounts::Initialize {
+                config: pda(&[b"config"]),
+                treasury: pda(&[b"treasury"]),
+                jackpot: pda(&[b"jackpot"]),
+                admin: admin.pubkey(),
+                program_data: Pubkey::find_program_address(
+                    &[dice_game::ID.as_ref()],
+                    &bpf_loader_upgradeable::ID,
+                )
+                .0,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dice_game::instruction::Initialize {
+                params: ConfigParams {
+                    oracle: game.oracle.pubkey(),
+                    fee_bps: 250,
+                    treasury: admin.pubkey(),
+                    min_entry_fee: 1,
+                    max_entry_fee: LAMPORTS_PER_SOL,
+                    max_players: 6,
+                    max_creator_rake_bps: 1_000,
+                    jackpot_bps: 1_000,
+                    jackpot_trigger: JackpotTrigger::Disabled,
+                    referral_bps: 0,
+                    stale_game_timeout: 24 * 60 * 60,
+                    min_deadlines: Deadlines {
+                        join_window: 60,
+                        roll_window: 60,
+                        claim_window: 60,
+                    },
+                    max_deadlines: Deadlines {
+                        join_window: 24 * 60 * 60,
+                        roll_window: 12 * 60 * 60,
+                        claim_window: 7 * 24 * 60 * 60,
+                    },
+                },
+            }
+            .data(),
+        };
+        game.send(initialize, &admin).await.unwrap();
+
+        let create = Instruction {
+            program_id: dice_game::ID,
+            accounts: dice_game::accounts::CreateGame {
+                game_account: game.game,
+                config: pda(&[b"config"]),
+                treasury: pda(&[b"treasury"]),
+                creator: game.creator.pubkey(),
+                escrow: pda(&[b"escrow", game.game.as_ref()]),
+                referral: pda(&[b"referral", game.creator.pubkey().as_ref()]),
+                mint: None,
+                vault: None,
+                creator_token_account: None,
+                token_program: None,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dice_game::instruction::CreateGame {
+                game_id,
+                entry_fee: ENTRY_FEE,
+                max_players: 2,
+                min_players: 2,
+                randomness: RandomnessSource::Oracle,
+                seed_commitment: None,
+                tie_break: TieBreak::Split,
+                payout_tiers: vec![10_000],
+                creator_rake_bps: 0,
+                referrer: None,
+                deadlines,
+            }
+            .data(),
+        };
+        let creator = game.creator.insecure_clone();
+        game.send(create, &creator).await.unwrap();
+
+        // The second player fills the game, which starts it
+        let join = Instruction {
+            program_id: dice_game::ID,
+            accounts: dice_game::accounts::JoinGame {
+                game_account: game.game,
+                player: game.opponent.pubkey(),
+                escrow: pda(&[b"escrow", game.game.as_ref()]),
+                referral: pda(&[b"referral", game.opponent.pubkey().as_ref()]),
+                mint: None,
+                vault: None,
+                player_token_account: None,
+                token_program: None,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dice_game::instruction::JoinGame {
+                seed_commitment: None,
+                referrer,
+            }
+            .data(),
+        };
+        let opponent = game.opponent.insecure_clone();
+        game.send(join, &opponent).await.unwrap();
+
+        game
+    }
+
+    // The test payer covers fees, so signers such as the oracle need no lamports. Each
+    // transaction gets a fresh blockhash, or a repeated one would be deduplicated by the bank
+    // instead of reaching the program
+    async fn send(&mut self, ix: Instruction, signer: &Keypair) -> Result<(), BanksClientError> {
+        let blockhash = self.ctx.get_new_latest_blockhash().await?;
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&self.ctx.payer.pubkey()),
+            &[&self.ctx.payer, signer],
+            blockhash,
+        );
+        self.ctx.banks_client.process_transaction(tx).await
+    }
+
+    fn request(&self, player: &Pubkey) -> Pubkey {
+        pda(&[b"randomness_request", self.game.as_ref(), player.as_ref(), &[0]])
+    }
+
+    async fn roll(&mut self, player: &Keypair) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: dice_game::ID,
+            accounts: dice_game::accounts::RollDice {
+                game_account: self.game,
+                player: player.pubkey(),
+                treasury: pda(&[b"treasury"]),
+                jackpot: pda(&[b"jackpot"]),
+                escrow: pda(&[b"escrow", self.game.as_ref()]),
+                randomness_request: Some(self.request(&player.pubkey())),
+                slot_hashes: sysvar::slot_hashes::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dice_game::instruction::RollDice {}.data(),
+        };
+        self.send(ix, player).await
+    }
+
+    async fn fulfill(&mut self, player: &Pubkey, oracle: &Keypair) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: dice_game::ID,
+            accounts: dice_game::accounts::FulfillRandomness {
+                config: pda(&[b"config"]),
+                oracle: oracle.pubkey(),
+                randomness_request: self.request(player),
+                player: *player,
+                game_account: self.game,
+                treasury: pda(&[b"treasury"]),
+                jackpot: pda(&[b"jackpot"]),
+                escrow: pda(&[b"escrow", self.game.as_ref()]),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: dice_game::instruction::FulfillRandomness {
+                randomness: rand_bytes(player),
+            }
+            .data(),
+        };
+        self.send(ix, oracle).await
+    }
+
+    async fn game_account(&mut self) -> GameAccount {
+        let account = self.ctx.banks_client.get_account(self.game).await.unwrap().unwrap();
+        GameAccount::try_deserialize(&mut &account.data[..]).unwrap()
+    }
+
+    async fn lamports(&mut self, key: &Pubkey) -> u64 {
+        self.ctx.banks_client.get_balance(*key).await.unwrap()
+    }
+}
+
+// Custom error code a failed instruction returned
+fn error_code(result: Result<(), BanksClientError>) -> u32 {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => code,
+        err => panic!("unexpected error {err:?}"),
+    }
+}
+
+// Deterministic stand-in for the oracle's output
+fn rand_bytes(player: &Pubkey) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hash(player.as_ref()).to_bytes()
+}
+
+#[tokio::test]
+async fn oracle_fulfilment_settles_the_game_and_closes_requests() {
+    let mut game = Game::start(None).await;
+    let creator = game.creator.insecure_clone();
+    let opponent = game.opponent.insecure_clone();
+
+    game.roll(&creator).await.unwrap();
+    game.roll(&opponent).await.unwrap();
+    let state = game.game_account().await;
+    assert!(state.rolls[0].is_none() && state.rolls[1].is_none());
+
+    let before = game.lamports(&opponent.pubkey()).await;
+    let oracle = game.oracle.insecure_clone();
+    game.fulfill(&creator.pubkey(), &oracle).await.unwrap();
+    game.fulfill(&opponent.pubkey(), &oracle).await.unwrap();
+
+    // Each request's rent goes back to the player who opened it
+    let request = game.request(&opponent.pubkey());
+    assert!(game.ctx.banks_client.get_account(request).await.unwrap().is_none());
+    assert!(game.lamports(&opponent.pubkey()).await > before);
+
+    let state = game.game_account().await;
+    assert_eq!(state.status, GameStatus::Completed);
+    assert!(state.winner.is_some());
+    for roll in state.rolls.iter().take(2) {
+        let total = roll.unwrap().total;
+        assert!((2..=12).contains(&total));
+    }
+}
+
+#[tokio::test]
+async fn only_the_configured_oracle_can_fulfil() {
+    let mut game = Game::start(None).await;
+    let creator = game.creator.insecure_clone();
+    game.roll(&creator).await.unwrap();
+
+    let impostor = Keypair::new();
+    let result = game.fulfill(&creator.pubkey(), &impostor).await;
+    assert_eq!(
+        error_code(result),
+        u32::from(dice_game::fulfill_randomness::CustomError::UnauthorizedOracle)
+    );
+
+    let oracle = game.oracle.insecure_clone();
+    game.fulfill(&creator.pubkey(), &oracle).await.unwrap();
+    // Fulfilling closed the request, so it can't be answered twice
+    let result = game.fulfill(&creator.pubkey(), &oracle).await;
+    assert_eq!(
+        error_code(result),
+        u32::from(anchor_lang::error::ErrorCode::AccountNotInitialized)
+    );
+    assert!(game.game_account().await.rolls[0].is_some());
+}
+
+#[tokio::test]
+async fn naming_a_referrer_opens_the_referral_pda() {
+    let referrer = Pubkey::new_unique();
+    let mut game = Game::start(Some(referrer)).await;
+    let opponent = game.opponent.pubkey();
+
+    let address = pda(&[b"referral", opponent.as_ref()]);
+    let account = game.ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    let referral = Referral::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!((referral.player, referral.referrer), (opponent, referrer));
+
+    // The creator named nobody, so has no referral PDA and no referrer on the seat
+    let creator = pda(&[b"referral", game.creator.pubkey().as_ref()]);
+    assert!(game.ctx.banks_client.get_account(creator).await.unwrap().is_none());
+    assert_eq!(game.game_account().await.referrers[..2], [None, Some(referrer)]);
+}
@@ -0,0 +1,233 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { DiceGame } from "../target/types/dice_game";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import { assert } from "chai";
+import { randomBytes } from "crypto";
+import { readFileSync } from "fs";
+import { join } from "path";
+
+// Local stand-in for a VRF oracle: a fixed keypair that answers randomness
+// requests with random bytes, so the request/fulfil flow runs on localnet.
+// Its secret key is committed, so these tests refuse to run against any other cluster.
+class MockOracle {
+  readonly keypair: Keypair;
+
+  constructor(private program: Program<DiceGame>) {
+    const secret = JSON.parse(
+      readFileSync(join(__dirname, "fixtures", "mock-oracle.json"), "utf8")
+    );
+    this.keypair = Keypair.fromSecretKey(Uint8Array.from(secret));
+  }
+
+  get publicKey(): PublicKey {
+    return this.keypair.publicKey;
+  }
+
+  async fulfill(
+    request: PublicKey,
+    gameAccount: PublicKey,
+    player: PublicKey,
+    signer = this.keypair
+  ) {
+    return this.program.methods
+      .fulfillRandomness([...randomBytes(32)])
+      .accountsPartial({
+        oracle: signer.publicKey,
+        randomnessRequest: request,
+        gameAccount,
+        player,
+      })
+      .signers([signer])
+      .rpc();
+  }
+}
+
+describe("dice-game oracle randomness", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DiceGame as Program<DiceGame>;
+  const oracle = new MockOracle(program);
+
+  const [configPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+
+  const entryFee = new anchor.BN(0.1 * anchor.web3.LAMPORTS_PER_SOL);
+
+  let creator: Keypair;
+  let opponent: Keypair;
+  let gamePDA: PublicKey;
+
+  const requestPDA = (player: PublicKey, round = 0) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("randomness_request"),
+        gamePDA.toBuffer(),
+        player.toBuffer(),
+        Buffer.from([round]),
+      ],
+      program.programId
+    )[0];
+
+  before(async function () {
+    // Anchor.toml targets devnet; registering the mock oracle there would hand the real
+    // config to a public keypair
+    const endpoint = new URL(provider.connection.rpcEndpoint).hostname;
+    if (!["localhost", "127.0.0.1", "0.0.0.0"].includes(endpoint)) {
+      this.skip();
+    }
+
+    // The config is a singleton, so point it at the mock oracle either way
+    const existing = await program.account.programConfig.fetchNullable(configPDA);
+    const params = {
+      oracle: oracle.publicKey,
+      feeBps: existing?.feeBps ?? 250,
+      treasury: existing?.treasury ?? provider.wallet.publicKey,
+      minEntryFee: existing?.minEntryFee ?? new anchor.BN(1),
+      maxEntryFee: existing?.maxEntryFee ?? new anchor.BN(100 * anchor.web3.LAMPORTS_PER_SOL),
+      maxPlayers: existing?.maxPlayers ?? 6,
+      maxCreatorRakeBps: existing?.maxCreatorRakeBps ?? 500,
+      jackpotBps: existing?.jackpotBps ?? 2_000,
+      jackpotTrigger: existing?.jackpotTrigger ?? { allDoubleSixes: {} },
+      referralBps: existing?.referralBps ?? 1_000,
+      staleGameTimeout: existing?.staleGameTimeout ?? new anchor.BN(24 * 60 * 60),
+      minDeadlines: existing?.minDeadlines ?? {
+        joinWindow: new anchor.BN(60),
+        rollWindow: new anchor.BN(60),
+        claimWindow: new anchor.BN(60 * 60),
+      },
+      maxDeadlines: existing?.maxDeadlines ?? {
+        joinWindow: new anchor.BN(7 * 24 * 60 * 60),
+        rollWindow: new anchor.BN(12 * 60 * 60),
+        claimWindow: new anchor.BN(90 * 24 * 60 * 60),
+      },
+    };
+    const method = existing
+      ? program.methods.updateConfig(params)
+      : program.methods.initialize(params);
+    await method
+      .accountsPartial({ config: configPDA, admin: provider.wallet.publicKey })
+      .rpc();
+  });
+
+  beforeEach(async () => {
+    creator = Keypair.generate();
+    opponent = Keypair.generate();
+    const gameId = new anchor.BN(Date.now());
+
+    [gamePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("game"), gameId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    for (const kp of [creator, opponent]) {
+      const sig = await provider.connection.requestAirdrop(
+        kp.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(sig);
+    }
+
+    await program.methods
+      .createGame(gameId, entryFee, 2, 2, { oracle: {} }, null, { split: {} }, [10_000], 0, null, {
+        joinWindow: new anchor.BN(24 * 60 * 60),
+        rollWindow: new anchor.BN(60 * 60),
+        claimWindow: new anchor.BN(30 * 24 * 60 * 60),
+      })
+      .accountsPartial({
+        gameAccount: gamePDA,
+        creator: creator.publicKey,
+        mint: null,
+        vault: null,
+        creatorTokenAccount: null,
+        tokenProgram: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([creator])
+      .rpc();
+
+    // Second player fills the game, which starts it
+    await program.methods
+      .joinGame(null, null)
+      .accountsPartial({
+        gameAccount: gamePDA,
+        player: opponent.publicKey,
+        mint: null,
+        vault: null,
+        playerTokenAccount: null,
+        tokenProgram: null,
+      })
+      .signers([opponent])
+      .rpc();
+  });
+
+  const roll = (player: Keypair) =>
+    program.methods
+      .rollDice()
+      .accountsPartial({
+        gameAccount: gamePDA,
+        player: player.publicKey,
+        randomnessRequest: requestPDA(player.publicKey),
+      })
+      .signers([player])
+      .rpc();
+
+  it("settles rolls once the oracle fulfils every request", async () => {
+    await roll(creator);
+    await roll(opponent);
+
+    let game = await program.account.gameAccount.fetch(gamePDA);
+    assert.isNull(game.rolls[0]);
+    assert.isNull(game.rolls[1]);
+
+    await oracle.fulfill(requestPDA(creator.publicKey), gamePDA, creator.publicKey);
+    await oracle.fulfill(requestPDA(opponent.publicKey), gamePDA, opponent.publicKey);
+
+    // Fulfilled requests are closed and their rent returned to the player
+    const request = await program.account.randomnessRequest.fetchNullable(
+      requestPDA(creator.publicKey)
+    );
+    assert.isNull(request);
+
+    game = await program.account.gameAccount.fetch(gamePDA);
+    assert.equal(game.status.completed !== undefined, true);
+    assert.isNotNull(game.winner);
+    for (const r of game.rolls.slice(0, 2)) {
+      assert.isAtLeast(r.total, 2);
+      assert.isAtMost(r.total, 12);
+    }
+  });
+
+  it("rejects fulfilment from a key other than the configured oracle", async () => {
+    await roll(creator);
+
+    const impostor = Keypair.generate();
+    try {
+      await oracle.fulfill(
+        requestPDA(creator.publicKey),
+        gamePDA,
+        creator.publicKey,
+        impostor
+      );
+      assert.fail("impostor fulfilled a randomness request");
+    } catch (err) {
+      assert.include(err.toString(), "UnauthorizedOracle");
+    }
+  });
+
+  it("rejects a second fulfilment of the same request", async () => {
+    await roll(creator);
+    await oracle.fulfill(requestPDA(creator.publicKey), gamePDA, creator.publicKey);
+
+    try {
+      await oracle.fulfill(requestPDA(creator.publicKey), gamePDA, creator.publicKey);
+      assert.fail("request fulfilled twice");
+    } catch (err) {
+      // The first fulfilment closed the request
+      assert.include(err.toString(), "AccountNotInitialized");
+    }
+  });
+});